@@ -1,13 +1,27 @@
 //! TagVer CLI - Command-line tool for minimalistic versioning using Git tags
 
 use clap::{ArgAction, CommandFactory, FromArgMatches, Parser};
+use regex::Regex;
 use std::path::PathBuf;
 use std::process::exit;
 
-use tagver::{calculate_version, Config, TagVerError, Verbosity, VersionPart};
+use tagver::config::{
+    Backend, HeightMetric, HeightPlacement, HeightSource, IdentifierValidation, TagPattern,
+    TagRequirement, TagSelection,
+};
+use tagver::metadata::MetadataPreset;
+use tagver::next::BumpKind;
+use tagver::requirement::{PrereleaseMatching, Requirement, VersionReq};
+use tagver::{
+    calculate_version, list_tags, next_versions, Config, ErrorKind, TagVerError, Verbosity,
+    Version, VersionPart,
+};
 use tracing::{debug, error, info, warn};
 use tracing_subscriber::FmtSubscriber;
 
+mod json_log;
+mod minver;
+
 shadow_rs::shadow!(build);
 const CLI_VERSION: &str = env!("TAGVER_CALCULATED_VERSION");
 
@@ -16,43 +30,204 @@ const CLI_VERSION: &str = env!("TAGVER_CALCULATED_VERSION");
 #[command(about = "Calculate version numbers from Git tags")]
 #[command(version = CLI_VERSION)]
 struct Args {
-    /// Working directory to analyze (defaults to current directory)
+    /// Working directory to analyze (defaults to current directory); write './tags' or '-- tags' for a directory named like a subcommand
     #[arg(default_value = ".")]
     working_directory: PathBuf,
 
+    #[command(subcommand)]
+    command: Option<Commands>,
+
     /// Tag prefix to filter tags (e.g., 'v' for 'v1.0.0')
-    #[arg(short = 't', long = "tag-prefix")]
+    #[arg(global = true, short = 't', long = "tag-prefix")]
     tag_prefix: Option<String>,
 
+    /// Tag pattern selecting version tags (e.g., 'myapp@*' or 'regex:^release-(?<version>.+)$'); may be repeated, tried in order
+    #[arg(global = true, short = 'P', long = "tag-pattern", value_parser = parse_tag_pattern)]
+    tag_patterns: Vec<TagPattern>,
+
+    /// Tag pattern excluding tags from consideration; may be repeated
+    #[arg(global = true, short = 'x', long = "exclude-tag-pattern", value_parser = parse_tag_pattern)]
+    exclude_tag_patterns: Vec<TagPattern>,
+
+    /// Kinds of tags accepted as version tags (any, annotated, signed, verified)
+    #[arg(global = true, long = "tag-requirement", value_parser = parse_tag_requirement)]
+    tag_requirement: Option<TagRequirement>,
+
+    /// Which tag wins when a commit has several (precedence, prefer-rtm, newest)
+    #[arg(global = true, long = "tag-selection", value_parser = parse_tag_selection)]
+    tag_selection: Option<TagSelection>,
+
+    /// SSH allowed signers file for '--tag-requirement verified' (defaults to gpg.ssh.allowedSignersFile)
+    #[arg(global = true, long = "allowed-signers")]
+    allowed_signers: Option<PathBuf>,
+
     /// Auto-increment policy for RTM versions (major, minor, patch)
-    #[arg(short = 'a', long = "auto-increment", value_parser = parse_version_part)]
+    #[arg(global = true, short = 'a', long = "auto-increment", value_parser = parse_version_part)]
     auto_increment: Option<VersionPart>,
 
     /// Default pre-release identifiers (e.g., 'alpha.0')
-    #[arg(short = 'p', long = "default-pre-release-identifiers")]
+    #[arg(global = true, short = 'p', long = "default-pre-release-identifiers")]
     default_prerelease_identifiers: Option<String>,
 
+    /// Default pre-release phase, MinVer's legacy form of '-p <PHASE>.0' (e.g., 'preview')
+    #[arg(
+        global = true,
+        short = 'd',
+        long = "default-pre-release-phase",
+        conflicts_with = "default_prerelease_identifiers"
+    )]
+    default_prerelease_phase: Option<String>,
+
+    /// Pre-release phases in order, for 'tagver bump promote' (e.g., 'alpha,beta,rc')
+    #[arg(global = true, long = "pre-release-phases", value_delimiter = ',')]
+    prerelease_phases: Vec<String>,
+
     /// Minimum major.minor version constraint (e.g., '1.0')
-    #[arg(short = 'm', long = "minimum-major-minor")]
+    #[arg(global = true, short = 'm', long = "minimum-major-minor")]
     minimum_major_minor: Option<String>,
 
     /// Ignore height in version calculation
-    #[arg(short = 'i', long = "ignore-height", action = ArgAction::SetTrue)]
+    #[arg(global = true, short = 'i', long = "ignore-height", action = ArgAction::SetTrue)]
     ignore_height: bool,
 
-    /// Build metadata to append to versions
-    #[arg(short = 'b', long = "build-metadata")]
+    /// How the height is measured (first-parent, minver, ancestry, no-merges)
+    #[arg(global = true, long = "height-metric", value_parser = parse_height_metric)]
+    height_metric: Option<HeightMetric>,
+
+    /// Regex for commit messages not counted in the height; may be repeated
+    #[arg(global = true, long = "height-exclude-message", value_name = "REGEX", value_parser = parse_regex)]
+    height_exclude_messages: Vec<Regex>,
+
+    /// Pattern for author emails whose commits are not counted in the height; may be repeated
+    #[arg(global = true, long = "height-exclude-author", value_name = "PATTERN", value_parser = parse_tag_pattern)]
+    height_exclude_authors: Vec<TagPattern>,
+
+    /// Where to place the height (prerelease, build-metadata)
+    #[arg(global = true, long = "height-placement", value_parser = parse_height_placement)]
+    height_placement: Option<HeightPlacement>,
+
+    /// Value written in place of the height (height, timestamp, commit-count)
+    #[arg(global = true, long = "height-source", value_parser = parse_height_source)]
+    height_source: Option<HeightSource>,
+
+    /// Zero-pad the height to this many digits
+    #[arg(global = true, long = "height-padding")]
+    height_padding: Option<usize>,
+
+    /// Identifier written before the height (e.g., 'dev' for 'alpha.0.dev.5')
+    #[arg(global = true, long = "height-identifier")]
+    height_identifier: Option<String>,
+
+    /// Build metadata to append to versions; may contain placeholders such as '{sha7}.{ci_build}'
+    #[arg(global = true, short = 'b', long = "build-metadata")]
     build_metadata: Option<String>,
 
+    /// Build metadata preset (ci, sha, local)
+    #[arg(global = true, long = "metadata-preset", value_parser = parse_metadata_preset, conflicts_with = "build_metadata")]
+    metadata_preset: Option<MetadataPreset>,
+
+    /// Handling of identifiers that are not valid SemVer (error, warn, sanitize)
+    #[arg(global = true, long = "identifier-validation", value_parser = parse_identifier_validation)]
+    identifier_validation: Option<IdentifierValidation>,
+
+    /// Fail with exit code 4 when there are no commits and 5 for a shallow clone
+    #[arg(global = true, long = "strict", action = ArgAction::SetTrue)]
+    strict: bool,
+
+    /// Revision to version instead of HEAD (e.g., a branch name or commit id, for bare mirrors)
+    #[arg(global = true, short = 'r', long = "revision")]
+    revision: Option<String>,
+
+    /// Inside a submodule, calculate the version of the superproject instead
+    #[arg(global = true, long = "use-superproject", action = ArgAction::SetTrue)]
+    use_superproject: bool,
+
+    /// Walk the original history, ignoring replace refs and grafts (like git --no-replace-objects)
+    #[arg(global = true, long = "no-replace-objects", action = ArgAction::SetTrue)]
+    no_replace_objects: bool,
+
+    /// Cache the result in .git/tagver-cache and reuse it while HEAD, tags and options are unchanged
+    #[arg(global = true, long = "cache", action = ArgAction::SetTrue)]
+    cache: bool,
+
+    /// Fetch tags of commits already present from a remote (default: origin) before calculating
+    #[arg(global = true, long = "remote-tags", value_name = "REMOTE", num_args = 0..=1, require_equals = true, default_missing_value = "origin")]
+    remote_tags: Option<String>,
+
+    /// Read the repository with gix or by running git (gix, git)
+    #[arg(global = true, long = "backend", value_parser = parse_backend)]
+    backend: Option<Backend>,
+
     /// Output format (text, json)
-    #[arg(short = 'f', long = "format", value_enum, default_value_t = OutputFormat::Text)]
+    #[arg(global = true, short = 'f', long = "format", value_enum, default_value_t = OutputFormat::Text)]
     format: OutputFormat,
 
-    /// Verbosity level (quiet, normal, verbose, debug, trace)
-    #[arg(short = 'v', long = "verbosity", value_parser = parse_verbosity)]
+    /// Log format (text, json); JSON logs are written to stderr, one object per line
+    #[arg(global = true, long = "log-format", value_enum, default_value_t = LogFormat::Text)]
+    log_format: LogFormat,
+
+    /// Reproduce MinVer's output text, log messages and exit codes
+    #[arg(global = true, long = "minver-compat", action = ArgAction::SetTrue)]
+    minver_compat: bool,
+
+    /// Verbosity level (quiet, normal, verbose, debug, trace, or MinVer's error, warn, info)
+    #[arg(global = true, short = 'v', long = "verbosity", value_parser = parse_verbosity)]
     verbosity: Option<Verbosity>,
 }
 
+#[derive(clap::Subcommand, Debug, Clone)]
+enum Commands {
+    /// List all tags with their commit, reachability from HEAD, and why they are skipped
+    Tags {
+        /// Working directory to analyze (defaults to the top-level working directory)
+        working_directory: Option<PathBuf>,
+    },
+    /// Preview the next major, minor, patch and prerelease versions from the base tag, and
+    /// the bump Conventional Commits would pick
+    Next {
+        /// Working directory to analyze (defaults to the top-level working directory)
+        working_directory: Option<PathBuf>,
+    },
+    /// Bump a version without reading the repository, e.g. 'tagver bump promote 1.0.0-beta.2'
+    Bump {
+        /// How to bump the version
+        #[arg(value_enum)]
+        operation: BumpOperation,
+        /// Version to bump, optionally with the tag prefix (e.g., 'v1.2.4-rc.1')
+        version: String,
+    },
+    /// Check that the calculated version satisfies a requirement, exiting with code 8 if not
+    Check {
+        /// Working directory to analyze (defaults to the top-level working directory)
+        working_directory: Option<PathBuf>,
+        /// Version range the version must be in (e.g., '>=2.0.0, <3')
+        #[arg(long = "require", value_parser = parse_version_req, required_unless_present = "require_stable")]
+        require: Option<VersionReq>,
+        /// Require a stable release, not a pre-release
+        #[arg(long = "require-stable", action = ArgAction::SetTrue)]
+        require_stable: bool,
+        /// Compare pre-releases with '--require' by precedence, so 2.1.0-alpha.0.3 matches '>=2.0.0'
+        #[arg(long = "include-prerelease", action = ArgAction::SetTrue)]
+        include_prerelease: bool,
+    },
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+enum BumpOperation {
+    /// Next major release (2.0.0-rc.1 -> 2.0.0, 2.1.0 -> 3.0.0)
+    Major,
+    /// Next minor release (1.3.0-rc.1 -> 1.3.0, 1.3.1 -> 1.4.0)
+    Minor,
+    /// Next patch release (1.2.4-rc.1 -> 1.2.4, 1.2.4 -> 1.2.5)
+    Patch,
+    /// Next pre-release (rc.1 -> rc.2, 1.2.3 -> 1.2.4-alpha.0)
+    Prerelease,
+    /// Next pre-release phase (alpha.3 -> beta.1, rc.2 -> release)
+    Promote,
+    /// Release of the version (1.0.0-rc.2 -> 1.0.0)
+    Finalize,
+}
+
 #[derive(clap::ValueEnum, Clone, Debug, Default)]
 enum OutputFormat {
     #[default]
@@ -60,12 +235,65 @@ enum OutputFormat {
     Json,
 }
 
+#[derive(clap::ValueEnum, Clone, Debug, Default)]
+enum LogFormat {
+    #[default]
+    Text,
+    Json,
+}
+
 fn parse_version_part(s: &str) -> Result<VersionPart, String> {
     s.parse::<VersionPart>()
 }
 
+fn parse_tag_pattern(s: &str) -> Result<TagPattern, String> {
+    s.parse::<TagPattern>()
+}
+
+fn parse_tag_requirement(s: &str) -> Result<TagRequirement, String> {
+    s.parse::<TagRequirement>()
+}
+
+fn parse_version_req(s: &str) -> Result<VersionReq, String> {
+    s.parse::<VersionReq>()
+        .map_err(|e| format!("Invalid version requirement '{}': {}", s, e))
+}
+
+fn parse_tag_selection(s: &str) -> Result<TagSelection, String> {
+    s.parse::<TagSelection>()
+}
+
+fn parse_height_placement(s: &str) -> Result<HeightPlacement, String> {
+    s.parse::<HeightPlacement>()
+}
+
+fn parse_height_metric(s: &str) -> Result<HeightMetric, String> {
+    s.parse::<HeightMetric>()
+}
+
+fn parse_regex(s: &str) -> Result<Regex, String> {
+    Regex::new(s).map_err(|e| format!("Invalid regex '{}': {}", s, e))
+}
+
+fn parse_height_source(s: &str) -> Result<HeightSource, String> {
+    s.parse::<HeightSource>()
+}
+
+fn parse_metadata_preset(s: &str) -> Result<MetadataPreset, String> {
+    s.parse::<MetadataPreset>()
+}
+
+fn parse_identifier_validation(s: &str) -> Result<IdentifierValidation, String> {
+    s.parse::<IdentifierValidation>()
+}
+
+fn parse_backend(s: &str) -> Result<Backend, String> {
+    s.parse::<Backend>()
+}
+
 fn parse_verbosity(s: &str) -> Result<Verbosity, String> {
     s.parse::<Verbosity>()
+        .or_else(|e| minver::parse_verbosity(s).ok_or(e))
 }
 
 fn main() {
@@ -73,28 +301,105 @@ fn main() {
 
     let mut cmd = Args::command();
     cmd = cmd.version(CLI_VERSION).long_version(long_ver);
-    let args = Args::from_arg_matches(&cmd.get_matches()).unwrap_or_else(|e| e.exit());
+    let matches = cmd
+        .try_get_matches()
+        .unwrap_or_else(|e| exit(report_usage_error(e)));
+    let args = Args::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    // A directory named like a subcommand can only be versioned as './<name>' or '-- <name>'
+    let shadowed_directory = matches
+        .subcommand_name()
+        .filter(|name| std::path::Path::new(name).is_dir());
+
+    // Build configuration from CLI arguments and environment variables
+    let config = build_config(&args);
+    let minver_compat = minver::compat_enabled(args.minver_compat);
 
     // Set up logging based on verbosity level
-    let verbosity = args.verbosity.clone().unwrap_or(Verbosity::Normal);
-    let tracing_level = match verbosity {
-        Verbosity::Quiet => tracing::Level::ERROR,
-        Verbosity::Normal => tracing::Level::WARN,
-        Verbosity::Verbose => tracing::Level::INFO,
-        Verbosity::Debug => tracing::Level::DEBUG,
-        Verbosity::Trace => tracing::Level::TRACE,
-    };
+    let builder = FmtSubscriber::builder().with_max_level(config.verbosity.level());
+    if let LogFormat::Json = args.log_format {
+        let subscriber = builder
+            .with_writer(std::io::stderr)
+            .fmt_fields(json_log::JsonFields)
+            .event_format(json_log::JsonFormat)
+            .finish();
+        tracing::subscriber::set_global_default(subscriber)
+    } else if minver_compat {
+        // MinVer logs to stderr, keeping stdout for the version
+        let subscriber = builder
+            .with_writer(std::io::stderr)
+            .event_format(minver::MinVerFormat)
+            .finish();
+        tracing::subscriber::set_global_default(subscriber)
+    } else if let OutputFormat::Json = args.format {
+        // Keep stdout for the JSON document
+        let subscriber = builder.with_writer(std::io::stderr).finish();
+        tracing::subscriber::set_global_default(subscriber)
+    } else {
+        tracing::subscriber::set_global_default(builder.finish())
+    }
+    .expect("setting default subscriber failed");
 
-    let subscriber = FmtSubscriber::builder()
-        .with_max_level(tracing_level)
-        .finish();
-    tracing::subscriber::set_global_default(subscriber).expect("setting default subscriber failed");
+    debug!("Using configuration: {:?}", config);
 
-    // Build configuration from CLI arguments and environment variables
-    let args_clone = args.clone();
-    let config = build_config(&args_clone);
+    if let Some(name) = shadowed_directory {
+        warn!(
+            "Running the '{0}' subcommand; to version the directory '{0}', use 'tagver ./{0}'",
+            name
+        );
+    }
 
-    debug!("Using configuration: {:?}", config);
+    if let Err(e) = config.validate() {
+        exit(report_error(&e, "Reading the configuration", &args.format));
+    }
+
+    if let Some(Commands::Tags { working_directory }) = &args.command {
+        let working_dir = working_directory
+            .clone()
+            .unwrap_or_else(|| args.working_directory.clone());
+        exit(print_tags(working_dir, &config, &args.format));
+    }
+
+    if let Some(Commands::Next { working_directory }) = &args.command {
+        let working_dir = working_directory
+            .clone()
+            .unwrap_or_else(|| args.working_directory.clone());
+        exit(print_next(working_dir, &config, &args.format));
+    }
+
+    if let Some(Commands::Bump { operation, version }) = &args.command {
+        exit(print_bump(*operation, version, &config, &args.format));
+    }
+
+    if let Some(Commands::Check {
+        working_directory,
+        require,
+        require_stable,
+        include_prerelease,
+    }) = &args.command
+    {
+        let working_dir = working_directory
+            .clone()
+            .unwrap_or_else(|| args.working_directory.clone());
+        let requirement = Requirement {
+            version_req: require.clone(),
+            require_stable: *require_stable,
+            prerelease: if *include_prerelease {
+                PrereleaseMatching::Include
+            } else {
+                PrereleaseMatching::Strict
+            },
+        };
+        exit(print_check(
+            working_dir,
+            &requirement,
+            &config,
+            &args.format,
+        ));
+    }
+
+    if minver_compat {
+        exit(print_minver_version(args.working_directory, &config));
+    }
 
     // Calculate the version
     let working_dir = args.working_directory.clone();
@@ -131,31 +436,343 @@ fn main() {
 
             0 // Success exit code
         }
+        Err(e) => report_error(&e, "Version calculation", &args.format),
+    };
+
+    exit(result);
+}
+
+/// The exit code of each kind of error, as documented in the README.
+fn exit_code(kind: ErrorKind) -> i32 {
+    match kind {
+        ErrorKind::Other => 1,
+        // The code clap exits with for invalid arguments
+        ErrorKind::InvalidConfig => 2,
+        ErrorKind::NotARepository => 3,
+        ErrorKind::NoCommits => 4,
+        ErrorKind::ShallowRepository => 5,
+        ErrorKind::InvalidTags => 6,
+        ErrorKind::Io => 7,
+        ErrorKind::RequirementNotMet => 8,
+    }
+}
+
+/// Log an error, and print it as a JSON object on stdout with JSON output, returning the
+/// exit code.
+fn report_error(e: &TagVerError, operation: &str, format: &OutputFormat) -> i32 {
+    let code = exit_code(e.kind());
+    match e {
+        TagVerError::GitRepoNotFound(path) => {
+            error!("'{}' is not a valid Git working directory", path);
+        }
+        _ => error!("{} failed: {}", operation, e),
+    }
+
+    if let OutputFormat::Json = format {
+        print_json_error(e.kind(), &e.to_string());
+    }
+
+    code
+}
+
+fn print_json_error(kind: ErrorKind, message: &str) {
+    let json = serde_json::json!({
+        "error": {
+            "code": kind.as_str(),
+            "exit_code": exit_code(kind),
+            "message": message
+        }
+    });
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&json).expect("Failed to serialize error")
+    );
+}
+
+/// Print a command-line parsing error, also as JSON on stdout when `--format json` is among
+/// the arguments, returning the exit code. Help and version output exit as usual.
+fn report_usage_error(e: clap::Error) -> i32 {
+    use clap::error::ErrorKind as ClapErrorKind;
+
+    if matches!(
+        e.kind(),
+        ClapErrorKind::DisplayHelp
+            | ClapErrorKind::DisplayVersion
+            | ClapErrorKind::DisplayHelpOnMissingArgumentOrSubcommand
+    ) {
+        e.exit();
+    }
+
+    let _ = e.print();
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if json_format_requested(&args) {
+        let rendered = e.render().to_string();
+        let message = rendered
+            .lines()
+            .next()
+            .unwrap_or_default()
+            .trim_start_matches("error: ");
+        print_json_error(ErrorKind::InvalidConfig, message);
+    }
+    exit_code(ErrorKind::InvalidConfig)
+}
+
+/// Whether `args` ask for JSON output, for errors raised before they are parsed.
+fn json_format_requested(args: &[String]) -> bool {
+    let is_json = |value: &str| value.eq_ignore_ascii_case("json");
+    args.iter().enumerate().any(|(i, arg)| match arg.as_str() {
+        "--format" | "-f" => args.get(i + 1).is_some_and(|value| is_json(value)),
+        _ => arg
+            .strip_prefix("--format=")
+            .or_else(|| arg.strip_prefix("-f").filter(|value| !value.is_empty()))
+            .is_some_and(|value| is_json(value.trim_start_matches('='))),
+    })
+}
+
+/// Print the version the way MinVer does, returning MinVer's exit code: a directory that
+/// isn't a Git working directory gets the default version with a warning, not an error.
+fn print_minver_version(working_dir: PathBuf, config: &Config) -> i32 {
+    if !working_dir.is_dir() {
+        error!(
+            code = minver::WORKING_DIRECTORY_NOT_FOUND,
+            "Working directory '{}' does not exist.",
+            working_dir.display()
+        );
+        return 2;
+    }
+
+    let result = match calculate_version(&working_dir, config) {
+        Err(TagVerError::GitRepoNotFound(_)) => {
+            let result = tagver::calculate_version_with_fallback(&working_dir, config);
+            if let Ok(result) = &result {
+                warn!(
+                    code = minver::NOT_A_WORKING_DIRECTORY,
+                    "'{}' is not a valid Git working directory. Using default version {}.",
+                    working_dir.display(),
+                    result
+                );
+            }
+            result
+        }
+        result => result,
+    };
+
+    match result {
+        Ok(result) => {
+            info!("Calculated version {}.", result);
+            println!("{}", result);
+            0
+        }
         Err(e) => {
-            match e {
-                TagVerError::GitRepoNotFound(path) => {
-                    error!("'{}' is not a valid Git working directory", path);
-                }
-                TagVerError::NoCommits => {
-                    info!("No commits found. Using default version.");
-                    println!("0.0.0-alpha.0");
-                }
-                TagVerError::ShallowRepo => {
-                    warn!("Shallow repository detected. Version calculation may be incorrect. Fetch full history with 'git fetch --unshallow'.");
-                    // Still try to calculate and return the result
-                    if let Ok(result) = calculate_version(args.working_directory, &config) {
-                        println!("{}", result);
-                    }
-                }
-                _ => {
-                    error!("Version calculation failed: {}", e);
+            error!("{}", e);
+            2
+        }
+    }
+}
+
+/// Print the tag listing of `tagver tags`, returning the exit code.
+fn print_tags(working_dir: PathBuf, config: &Config, format: &OutputFormat) -> i32 {
+    let tags = match list_tags(working_dir, config) {
+        Ok(tags) => tags,
+        Err(e) => return report_error(&e, "Listing tags", format),
+    };
+
+    match format {
+        OutputFormat::Text => {
+            let rows: Vec<[String; 5]> = tags
+                .iter()
+                .map(|tag| {
+                    [
+                        tag.tag_name.clone(),
+                        tag.target
+                            .map(|id| id.to_hex_with_len(7).to_string())
+                            .unwrap_or_else(|| "-".to_string()),
+                        if tag.reachable { "yes" } else { "no" }.to_string(),
+                        if tag.annotated { "yes" } else { "no" }.to_string(),
+                        match (&tag.skipped, &tag.version) {
+                            (Some(reason), _) => format!("skipped: {}", reason),
+                            (None, Some(version)) => version.to_string(),
+                            (None, None) => String::new(),
+                        },
+                    ]
+                })
+                .collect();
+
+            let header = ["TAG", "COMMIT", "REACHABLE", "ANNOTATED", "VERSION"];
+            let mut widths = header.map(str::len);
+            for row in &rows {
+                for (width, cell) in widths.iter_mut().zip(row) {
+                    *width = (*width).max(cell.len());
                 }
             }
-            2 // Error exit code
+
+            for row in std::iter::once(header.map(String::from)).chain(rows) {
+                let line = row
+                    .iter()
+                    .zip(widths)
+                    .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+                    .collect::<Vec<_>>()
+                    .join("  ");
+                println!("{}", line.trim_end());
+            }
+        }
+        OutputFormat::Json => {
+            let json: Vec<_> = tags
+                .iter()
+                .map(|tag| {
+                    serde_json::json!({
+                        "name": tag.tag_name,
+                        "version": tag.version.as_ref().map(|v| v.to_string()),
+                        "commit": tag.target.map(|id| id.to_string()),
+                        "reachable": tag.reachable,
+                        "annotated": tag.annotated,
+                        "skipped": tag.skipped.as_ref().map(|reason| reason.to_string()),
+                    })
+                })
+                .collect();
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&json).expect("Failed to serialize tags")
+            );
         }
+    }
+
+    0
+}
+
+/// Print the next versions of `tagver next`, returning the exit code.
+fn print_next(working_dir: PathBuf, config: &Config, format: &OutputFormat) -> i32 {
+    let next = match next_versions(working_dir, config) {
+        Ok(next) => next,
+        Err(e) => return report_error(&e, "Previewing next versions", format),
     };
+    let conventional = next.conventional.map(|kind| (kind, next.get(kind)));
 
-    exit(result);
+    match format {
+        OutputFormat::Text => {
+            match &next.base_tag {
+                Some(tag) => println!(
+                    "base:         {} (tag {}, height {})",
+                    next.base, tag, next.height
+                ),
+                None => println!("base:         none (height {})", next.height),
+            }
+            for kind in [
+                BumpKind::Major,
+                BumpKind::Minor,
+                BumpKind::Patch,
+                BumpKind::Prerelease,
+            ] {
+                println!("{:<13} {}", format!("{}:", kind), next.get(kind));
+            }
+            match conventional {
+                Some((kind, version)) => println!("conventional: {} ({})", kind, version),
+                None => println!("conventional: none (no commits since the base tag)"),
+            }
+        }
+        OutputFormat::Json => {
+            let json = serde_json::json!({
+                "base_tag": next.base_tag,
+                "base": next.base.to_string(),
+                "height": next.height,
+                "major": next.major.to_string(),
+                "minor": next.minor.to_string(),
+                "patch": next.patch.to_string(),
+                "prerelease": next.prerelease.to_string(),
+                "conventional": conventional.map(|(kind, version)| serde_json::json!({
+                    "bump": kind.to_string(),
+                    "version": version.to_string(),
+                })),
+            });
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&json).expect("Failed to serialize next versions")
+            );
+        }
+    }
+
+    0
+}
+
+fn print_check(
+    working_dir: PathBuf,
+    requirement: &Requirement,
+    config: &Config,
+    format: &OutputFormat,
+) -> i32 {
+    let result = calculate_version(working_dir, config).and_then(|result| {
+        result.check(requirement)?;
+        Ok(result)
+    });
+    let result = match result {
+        Ok(result) => result,
+        Err(e) => return report_error(&e, "Version check", format),
+    };
+    info!("Version {} satisfies the requirement", result.version);
+
+    match format {
+        OutputFormat::Text => println!("{}", result.version),
+        OutputFormat::Json => println!(
+            "{}",
+            serde_json::to_string_pretty(&result).expect("Failed to serialize result")
+        ),
+    }
+
+    0
+}
+
+fn print_bump(
+    operation: BumpOperation,
+    version: &str,
+    config: &Config,
+    format: &OutputFormat,
+) -> i32 {
+    let bumped = bump_version(operation, version, config);
+    let bumped = match bumped {
+        Ok(bumped) => bumped,
+        Err(e) => return report_error(&e, "Bumping the version", format),
+    };
+
+    match format {
+        OutputFormat::Text => println!("{}", bumped),
+        OutputFormat::Json => {
+            let json = serde_json::json!({
+                "version": bumped.to_string(),
+                "previous": version,
+            });
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&json).expect("Failed to serialize version")
+            );
+        }
+    }
+
+    0
+}
+
+fn bump_version(
+    operation: BumpOperation,
+    version: &str,
+    config: &Config,
+) -> Result<Version, TagVerError> {
+    // A malformed version is bad input, not a bad tag
+    let version = Version::parse(version.strip_prefix(&config.tag_prefix).unwrap_or(version))
+        .map_err(|e| match e {
+            TagVerError::InvalidSemver(message) => TagVerError::InvalidVersion(message),
+            e => e,
+        })?;
+
+    match operation {
+        BumpOperation::Major => version.next_release(&VersionPart::Major),
+        BumpOperation::Minor => version.next_release(&VersionPart::Minor),
+        BumpOperation::Patch => version.next_release(&VersionPart::Patch),
+        BumpOperation::Prerelease => version.increment_prerelease(
+            &config.auto_increment,
+            &config.default_prerelease_identifiers,
+        ),
+        BumpOperation::Promote => version.promote(&config.prerelease_phases),
+        BumpOperation::Finalize => Ok(version.finalize()),
+    }
 }
 
 fn build_config(args: &Args) -> Config {
@@ -171,6 +788,26 @@ fn build_config(args: &Args) -> Config {
         config.tag_prefix = prefix.clone();
     }
 
+    if !args.tag_patterns.is_empty() {
+        config.tag_patterns = args.tag_patterns.clone();
+    }
+
+    if !args.exclude_tag_patterns.is_empty() {
+        config.exclude_tag_patterns = args.exclude_tag_patterns.clone();
+    }
+
+    if let Some(requirement) = &args.tag_requirement {
+        config.tag_requirement = requirement.clone();
+    }
+
+    if let Some(selection) = args.tag_selection {
+        config.tag_selection = selection;
+    }
+
+    if let Some(allowed_signers) = &args.allowed_signers {
+        config.allowed_signers = Some(allowed_signers.clone());
+    }
+
     if let Some(auto_inc) = &args.auto_increment {
         config.auto_increment = auto_inc.clone();
     }
@@ -180,6 +817,14 @@ fn build_config(args: &Args) -> Config {
             identifiers.split('.').map(|s| s.to_string()).collect();
     }
 
+    if let Some(phase) = &args.default_prerelease_phase {
+        config.default_prerelease_identifiers = minver::phase_identifiers(phase);
+    }
+
+    if !args.prerelease_phases.is_empty() {
+        config.prerelease_phases = args.prerelease_phases.clone();
+    }
+
     if let Some(min_mm) = &args.minimum_major_minor {
         if let Ok(minor_major) = tagver::config::MajorMinor::parse(min_mm) {
             config.minimum_major_minor = Some(minor_major);
@@ -190,10 +835,74 @@ fn build_config(args: &Args) -> Config {
         config.ignore_height = true;
     }
 
+    if let Some(metric) = args.height_metric {
+        config.height_metric = metric;
+    }
+
+    if !args.height_exclude_messages.is_empty() {
+        config.height_exclude_messages = args.height_exclude_messages.clone();
+    }
+
+    if !args.height_exclude_authors.is_empty() {
+        config.height_exclude_authors = args.height_exclude_authors.clone();
+    }
+
+    if let Some(placement) = &args.height_placement {
+        config.height_format.placement = placement.clone();
+    }
+
+    if let Some(source) = &args.height_source {
+        config.height_format.source = source.clone();
+    }
+
+    if let Some(padding) = args.height_padding {
+        config.height_format.padding = padding;
+    }
+
+    if let Some(identifier) = &args.height_identifier {
+        config.height_format.identifier = Some(identifier.clone());
+    }
+
     if let Some(build_meta) = &args.build_metadata {
         config.build_metadata = Some(build_meta.clone());
     }
 
+    if let Some(preset) = &args.metadata_preset {
+        config.build_metadata = Some(preset.template().to_string());
+    }
+
+    if let Some(validation) = &args.identifier_validation {
+        config.identifier_validation = validation.clone();
+    }
+
+    if args.strict {
+        config.strict = true;
+    }
+
+    if let Some(revision) = &args.revision {
+        config.revision = Some(revision.clone());
+    }
+
+    if args.use_superproject {
+        config.use_superproject = true;
+    }
+
+    if args.no_replace_objects {
+        config.use_replace_refs = false;
+    }
+
+    if args.cache {
+        config.cache = true;
+    }
+
+    if let Some(remote) = &args.remote_tags {
+        config.remote_tags = Some(remote.clone());
+    }
+
+    if let Some(backend) = args.backend {
+        config.backend = backend;
+    }
+
     if let Some(verbosity) = &args.verbosity {
         config.verbosity = verbosity.clone();
     }
@@ -204,12 +913,46 @@ fn build_config(args: &Args) -> Config {
 fn apply_env_vars(config: &mut Config) {
     use std::env;
 
+    // MinVer's variables are fallbacks for the TAGVER_* ones
+    minver::apply_env_vars(config);
+
     if let Ok(tag_prefix) = env::var("TAGVER_TAGPREFIX") {
         if !tag_prefix.is_empty() {
             config.tag_prefix = tag_prefix;
         }
     }
 
+    // Git ref names cannot contain spaces, so patterns are whitespace-separated
+    if let Ok(patterns) = env::var("TAGVER_TAGPATTERN") {
+        if let Ok(patterns) = parse_tag_pattern_list(&patterns) {
+            config.tag_patterns = patterns;
+        }
+    }
+
+    if let Ok(patterns) = env::var("TAGVER_EXCLUDETAGPATTERN") {
+        if let Ok(patterns) = parse_tag_pattern_list(&patterns) {
+            config.exclude_tag_patterns = patterns;
+        }
+    }
+
+    if let Ok(requirement) = env::var("TAGVER_TAGREQUIREMENT") {
+        if let Ok(requirement) = requirement.parse::<TagRequirement>() {
+            config.tag_requirement = requirement;
+        }
+    }
+
+    if let Ok(selection) = env::var("TAGVER_TAGSELECTION") {
+        if let Ok(selection) = selection.parse::<TagSelection>() {
+            config.tag_selection = selection;
+        }
+    }
+
+    if let Ok(allowed_signers) = env::var("TAGVER_ALLOWEDSIGNERS") {
+        if !allowed_signers.is_empty() {
+            config.allowed_signers = Some(PathBuf::from(allowed_signers));
+        }
+    }
+
     if let Ok(auto_inc) = env::var("TAGVER_AUTOINCREMENT") {
         if let Ok(part) = auto_inc.parse::<VersionPart>() {
             config.auto_increment = part;
@@ -223,6 +966,12 @@ fn apply_env_vars(config: &mut Config) {
         }
     }
 
+    if let Ok(phases) = env::var("TAGVER_PRERELEASEPHASES") {
+        if !phases.is_empty() {
+            config.prerelease_phases = phases.split(',').map(|s| s.trim().to_string()).collect();
+        }
+    }
+
     if let Ok(min_mm) = env::var("TAGVER_MINIMUMMAJORMINOR") {
         if let Ok(minor_major) = tagver::config::MajorMinor::parse(&min_mm) {
             config.minimum_major_minor = Some(minor_major);
@@ -235,12 +984,104 @@ fn apply_env_vars(config: &mut Config) {
         }
     }
 
+    if let Ok(metric) = env::var("TAGVER_HEIGHTMETRIC") {
+        if let Ok(metric) = metric.parse::<HeightMetric>() {
+            config.height_metric = metric;
+        }
+    }
+
+    if let Ok(message) = env::var("TAGVER_HEIGHTEXCLUDEMESSAGE") {
+        if !message.is_empty() {
+            if let Ok(regex) = parse_regex(&message) {
+                config.height_exclude_messages = vec![regex];
+            }
+        }
+    }
+
+    if let Ok(patterns) = env::var("TAGVER_HEIGHTEXCLUDEAUTHOR") {
+        if let Ok(patterns) = parse_tag_pattern_list(&patterns) {
+            config.height_exclude_authors = patterns;
+        }
+    }
+
+    if let Ok(placement) = env::var("TAGVER_HEIGHTPLACEMENT") {
+        if let Ok(placement) = placement.parse::<HeightPlacement>() {
+            config.height_format.placement = placement;
+        }
+    }
+
+    if let Ok(source) = env::var("TAGVER_HEIGHTSOURCE") {
+        if let Ok(source) = source.parse::<HeightSource>() {
+            config.height_format.source = source;
+        }
+    }
+
+    if let Ok(padding) = env::var("TAGVER_HEIGHTPADDING") {
+        if let Ok(padding) = padding.parse::<usize>() {
+            config.height_format.padding = padding;
+        }
+    }
+
+    if let Ok(identifier) = env::var("TAGVER_HEIGHTIDENTIFIER") {
+        if !identifier.is_empty() {
+            config.height_format.identifier = Some(identifier);
+        }
+    }
+
+    if let Ok(preset) = env::var("TAGVER_METADATAPRESET") {
+        if let Ok(preset) = preset.parse::<MetadataPreset>() {
+            config.build_metadata = Some(preset.template().to_string());
+        }
+    }
+
     if let Ok(build_meta) = env::var("TAGVER_BUILDMETADATA") {
         if !build_meta.is_empty() {
             config.build_metadata = Some(build_meta);
         }
     }
 
+    if let Ok(validation) = env::var("TAGVER_IDENTIFIERVALIDATION") {
+        if let Ok(validation) = validation.parse::<IdentifierValidation>() {
+            config.identifier_validation = validation;
+        }
+    }
+
+    if let Ok(strict) = env::var("TAGVER_STRICT") {
+        if let Ok(value) = strict.parse::<bool>() {
+            config.strict = value;
+        }
+    }
+
+    if let Ok(revision) = env::var("TAGVER_REVISION") {
+        if !revision.is_empty() {
+            config.revision = Some(revision);
+        }
+    }
+
+    if let Ok(use_superproject) = env::var("TAGVER_USESUPERPROJECT") {
+        if let Ok(value) = use_superproject.parse::<bool>() {
+            config.use_superproject = value;
+        }
+    }
+
+    if let Ok(cache) = env::var("TAGVER_CACHE") {
+        if let Ok(value) = cache.parse::<bool>() {
+            config.cache = value;
+        }
+    }
+
+    if let Ok(remote) = env::var("TAGVER_REMOTETAGS") {
+        if !remote.is_empty() {
+            config.remote_tags = Some(remote);
+        }
+    }
+
+    if let Ok(backend) = env::var("TAGVER_BACKEND") {
+        if let Ok(backend) = backend.parse::<Backend>() {
+            config.backend = backend;
+        }
+    }
+
     if let Ok(verbosity) = env::var("TAGVER_VERBOSITY") {
         if let Ok(level) = verbosity.parse::<Verbosity>() {
             config.verbosity = level;
@@ -248,6 +1089,10 @@ fn apply_env_vars(config: &mut Config) {
     }
 }
 
+fn parse_tag_pattern_list(s: &str) -> Result<Vec<TagPattern>, String> {
+    s.split_whitespace().map(parse_tag_pattern).collect()
+}
+
 fn long_version() -> String {
     format!(
         "{version}\ncommit: {commit} ({date})\nbuild: {build}\nrustc: {rustc}",
@@ -267,13 +1112,39 @@ mod tests {
     fn test_config_from_args() {
         let args = Args {
             working_directory: PathBuf::from("/tmp"),
+            command: None,
             tag_prefix: Some("v".to_string()),
+            tag_patterns: vec!["release-*".parse().unwrap()],
+            exclude_tag_patterns: vec!["*-rc.*".parse().unwrap()],
+            tag_requirement: Some(TagRequirement::Verified),
+            tag_selection: Some(TagSelection::PreferRtm),
+            allowed_signers: Some(PathBuf::from("/etc/allowed_signers")),
             auto_increment: Some(VersionPart::Minor),
             default_prerelease_identifiers: Some("beta.0".to_string()),
+            default_prerelease_phase: None,
+            prerelease_phases: Vec::new(),
             minimum_major_minor: Some("2.1".to_string()),
             ignore_height: true,
+            height_metric: Some(HeightMetric::Ancestry),
+            height_exclude_messages: vec![Regex::new(r"^chore\(deps\)").unwrap()],
+            height_exclude_authors: vec!["*[bot]@*".parse().unwrap()],
+            height_placement: Some(HeightPlacement::BuildMetadata),
+            height_source: Some(HeightSource::CommitCount),
+            height_padding: Some(4),
+            height_identifier: Some("dev".to_string()),
             build_metadata: Some("build.123".to_string()),
+            metadata_preset: None,
+            identifier_validation: Some(IdentifierValidation::Sanitize),
+            strict: true,
+            revision: Some("main".to_string()),
+            use_superproject: true,
+            no_replace_objects: true,
+            cache: true,
+            remote_tags: Some("upstream".to_string()),
+            backend: Some(Backend::Git),
             format: OutputFormat::Text,
+            log_format: LogFormat::Json,
+            minver_compat: false,
             verbosity: Some(Verbosity::Debug),
         };
 
@@ -281,13 +1152,59 @@ mod tests {
 
         assert_eq!(config.work_dir, PathBuf::from("/tmp"));
         assert_eq!(config.tag_prefix, "v");
+        assert_eq!(config.tag_patterns[0].to_string(), "glob:release-*");
+        assert_eq!(config.exclude_tag_patterns[0].to_string(), "glob:*-rc.*");
+        assert_eq!(config.tag_requirement, TagRequirement::Verified);
+        assert_eq!(config.tag_selection, TagSelection::PreferRtm);
+        assert_eq!(
+            config.allowed_signers,
+            Some(PathBuf::from("/etc/allowed_signers"))
+        );
         assert_eq!(config.auto_increment, VersionPart::Minor);
         assert_eq!(config.default_prerelease_identifiers, vec!["beta", "0"]);
         assert!(config.ignore_height);
+        assert_eq!(config.height_metric, HeightMetric::Ancestry);
+        assert_eq!(
+            config.height_exclude_messages[0].as_str(),
+            r"^chore\(deps\)"
+        );
+        assert_eq!(
+            config.height_exclude_authors[0].to_string(),
+            "glob:*[bot]@*"
+        );
+        assert_eq!(
+            config.height_format.placement,
+            HeightPlacement::BuildMetadata
+        );
+        assert_eq!(config.height_format.source, HeightSource::CommitCount);
+        assert_eq!(config.height_format.padding, 4);
+        assert_eq!(config.height_format.identifier, Some("dev".to_string()));
         assert_eq!(config.build_metadata, Some("build.123".to_string()));
+        assert_eq!(config.identifier_validation, IdentifierValidation::Sanitize);
+        assert!(config.strict);
+        assert_eq!(config.revision, Some("main".to_string()));
+        assert!(config.use_superproject);
+        assert!(!config.use_replace_refs);
+        assert!(config.cache);
+        assert_eq!(config.remote_tags, Some("upstream".to_string()));
+        assert_eq!(config.backend, Backend::Git);
         assert_eq!(config.verbosity, tagver::config::Verbosity::Debug);
     }
 
+    #[test]
+    fn test_json_format_requested() {
+        let requested = |args: &[&str]| {
+            json_format_requested(&args.iter().map(|s| s.to_string()).collect::<Vec<_>>())
+        };
+        assert!(requested(&["--format", "json"]));
+        assert!(requested(&["--format=JSON"]));
+        assert!(requested(&["-f", "json", "tags"]));
+        assert!(requested(&["-fjson"]));
+        assert!(!requested(&["--format", "text"]));
+        assert!(!requested(&["--tag-prefix", "json"]));
+        assert!(!requested(&["-f"]));
+    }
+
     #[test]
     fn test_env_var_sets_verbosity() {
         // Preserve previous value to avoid leaking state
@@ -296,13 +1213,39 @@ mod tests {
 
         let args = Args {
             working_directory: PathBuf::from("."),
+            command: None,
             tag_prefix: None,
+            tag_patterns: Vec::new(),
+            exclude_tag_patterns: Vec::new(),
+            tag_requirement: None,
+            tag_selection: None,
+            allowed_signers: None,
             auto_increment: None,
             default_prerelease_identifiers: None,
+            default_prerelease_phase: None,
+            prerelease_phases: Vec::new(),
             minimum_major_minor: None,
             ignore_height: false,
+            height_metric: None,
+            height_exclude_messages: Vec::new(),
+            height_exclude_authors: Vec::new(),
+            height_placement: None,
+            height_source: None,
+            height_padding: None,
+            height_identifier: None,
             build_metadata: None,
+            metadata_preset: None,
+            identifier_validation: None,
+            strict: false,
+            revision: None,
+            use_superproject: false,
+            no_replace_objects: false,
+            cache: false,
+            remote_tags: None,
+            backend: None,
             format: OutputFormat::Text,
+            log_format: LogFormat::Text,
+            minver_compat: false,
             verbosity: None,
         };
 
@@ -0,0 +1,159 @@
+//! MinVer compatibility - `MINVER*` environment variables and MinVer-style log output
+
+use std::env;
+use std::fmt;
+
+use tagver::{Config, Verbosity, VersionPart};
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::fmt::format::Writer;
+use tracing_subscriber::fmt::{FmtContext, FormatEvent, FormatFields};
+use tracing_subscriber::registry::LookupSpan;
+
+/// Code of the warning logged when the working directory is not a Git working directory.
+pub const NOT_A_WORKING_DIRECTORY: u32 = 1001;
+/// Code of the error logged when the working directory does not exist.
+pub const WORKING_DIRECTORY_NOT_FOUND: u32 = 1002;
+
+/// Parse a MinVer verbosity: the CLI levels (`error`, `warn`, `info`, `debug`, `trace`) and
+/// the MSBuild levels (`quiet`, `minimal`, `normal`, `detailed`, `diagnostic`), with their
+/// abbreviations.
+pub fn parse_verbosity(s: &str) -> Option<Verbosity> {
+    match s.to_lowercase().as_str() {
+        "e" | "error" | "q" | "quiet" => Some(Verbosity::Quiet),
+        "w" | "warn" | "m" | "minimal" => Some(Verbosity::Normal),
+        "i" | "info" | "n" | "normal" => Some(Verbosity::Verbose),
+        "d" | "debug" | "detailed" => Some(Verbosity::Debug),
+        "t" | "trace" | "diag" | "diagnostic" => Some(Verbosity::Trace),
+        _ => None,
+    }
+}
+
+/// The pre-release identifiers MinVer uses for a default pre-release phase, e.g. `preview.0`.
+pub fn phase_identifiers(phase: &str) -> Vec<String> {
+    vec![phase.to_string(), "0".to_string()]
+}
+
+/// Whether MinVer compatibility is enabled by the flag or `TAGVER_MINVERCOMPAT`.
+pub fn compat_enabled(flag: bool) -> bool {
+    flag || env::var("TAGVER_MINVERCOMPAT").is_ok_and(|value| is_true(&value))
+}
+
+/// Apply the `MINVER*` environment variables. They are fallbacks, so this runs before the
+/// `TAGVER_*` variables are applied.
+pub fn apply_env_vars(config: &mut Config) {
+    if let Ok(tag_prefix) = env::var("MINVERTAGPREFIX") {
+        if !tag_prefix.is_empty() {
+            config.tag_prefix = tag_prefix;
+        }
+    }
+
+    if let Ok(auto_inc) = env::var("MINVERAUTOINCREMENT") {
+        if let Ok(part) = auto_inc.parse::<VersionPart>() {
+            config.auto_increment = part;
+        }
+    }
+
+    if let Ok(phase) = env::var("MINVERDEFAULTPRERELEASEPHASE") {
+        if !phase.is_empty() {
+            config.default_prerelease_identifiers = phase_identifiers(&phase);
+        }
+    }
+
+    // MinVer 5 replaced the phase with the identifiers, which win when both are set
+    if let Ok(identifiers) = env::var("MINVERDEFAULTPRERELEASEIDENTIFIERS") {
+        if !identifiers.is_empty() {
+            config.default_prerelease_identifiers =
+                identifiers.split('.').map(|s| s.to_string()).collect();
+        }
+    }
+
+    if let Ok(min_mm) = env::var("MINVERMINIMUMMAJORMINOR") {
+        if let Ok(minor_major) = tagver::config::MajorMinor::parse(&min_mm) {
+            config.minimum_major_minor = Some(minor_major);
+        }
+    }
+
+    if let Ok(ignore_height) = env::var("MINVERIGNOREHEIGHT") {
+        if !ignore_height.is_empty() {
+            config.ignore_height = is_true(&ignore_height);
+        }
+    }
+
+    if let Ok(build_meta) = env::var("MINVERBUILDMETADATA") {
+        if !build_meta.is_empty() {
+            config.build_metadata = Some(build_meta);
+        }
+    }
+
+    if let Ok(verbosity) = env::var("MINVERVERBOSITY") {
+        if let Some(level) = parse_verbosity(&verbosity) {
+            config.verbosity = level;
+        }
+    }
+}
+
+/// MSBuild booleans are case-insensitive.
+fn is_true(value: &str) -> bool {
+    value.eq_ignore_ascii_case("true")
+}
+
+/// Formats events the way MinVer logs: `MinVer: <message>`, with warnings and errors in the
+/// MSBuild canonical form `MinVer: warning MINVER1001: <message>`. The code is taken from
+/// a `code` field of the event.
+pub struct MinVerFormat;
+
+impl<S, N> FormatEvent<S, N> for MinVerFormat
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(
+        &self,
+        _ctx: &FmtContext<'_, S, N>,
+        mut writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> fmt::Result {
+        let mut fields = EventFields::default();
+        event.record(&mut fields);
+
+        write!(writer, "MinVer: ")?;
+        let category = match *event.metadata().level() {
+            Level::ERROR => Some("error"),
+            Level::WARN => Some("warning"),
+            _ => None,
+        };
+        match (category, fields.code) {
+            (Some(category), Some(code)) => write!(writer, "{} MINVER{:04}: ", category, code)?,
+            (Some(category), None) => write!(writer, "{}: ", category)?,
+            (None, _) => {}
+        }
+        writeln!(writer, "{}", fields.message)
+    }
+}
+
+#[derive(Default)]
+struct EventFields {
+    message: String,
+    code: Option<u64>,
+}
+
+impl Visit for EventFields {
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        if field.name() == "code" {
+            self.code = Some(value);
+        }
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        if field.name() == "code" {
+            self.code = u64::try_from(value).ok();
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            self.message = format!("{:?}", value);
+        }
+    }
+}
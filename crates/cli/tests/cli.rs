@@ -105,7 +105,7 @@ fn test_non_git_directory() {
     tagver_cmd()
         .current_dir(temp.path())
         .assert()
-        .code(2)
+        .code(3)
         // Error message is emitted to stdout by tracing
         .stdout(predicate::str::contains(
             "Could not find a git repository in '.' or in any of its parents",
@@ -321,3 +321,918 @@ fn test_json_output() {
         .stdout(predicate::str::contains(r#""pre_release": []"#))
         .stdout(predicate::str::contains(r#""build_metadata": null"#));
 }
+
+#[test]
+fn test_tag_pattern() {
+    let temp = create_git_repo_with_tag("myapp@1.4.0");
+
+    tagver_cmd()
+        .current_dir(temp.path())
+        .arg("--tag-pattern")
+        .arg("myapp@*")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("1.4.0"));
+}
+
+#[test]
+fn test_env_var_tag_pattern() {
+    let temp = create_git_repo_with_tag("release-1.4.0");
+
+    tagver_cmd()
+        .current_dir(temp.path())
+        .env("TAGVER_TAGPATTERN", "myapp@* release-*")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("1.4.0"));
+}
+
+#[test]
+fn test_height_format() {
+    let temp = create_git_repo_with_tag("1.0.0");
+    let repo_path = temp.path();
+
+    StdCommand::new("git")
+        .arg("commit")
+        .arg("--allow-empty")
+        .arg("-m")
+        .arg("feat: new feature")
+        .current_dir(repo_path)
+        .assert()
+        .success();
+
+    tagver_cmd()
+        .current_dir(repo_path)
+        .arg("--height-identifier")
+        .arg("dev")
+        .arg("--height-padding")
+        .arg("3")
+        .arg("--height-placement")
+        .arg("build-metadata")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("1.0.1-alpha.0+dev.001"));
+
+    // Leading zeros aren't valid in the prerelease, unless only warned about
+    tagver_cmd()
+        .current_dir(repo_path)
+        .arg("--height-padding")
+        .arg("3")
+        .assert()
+        .code(2)
+        .stdout(predicate::str::contains(
+            "place the height in build metadata",
+        ));
+
+    tagver_cmd()
+        .current_dir(repo_path)
+        .arg("--height-padding")
+        .arg("3")
+        .arg("--identifier-validation")
+        .arg("warn")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("1.0.1-alpha.0.001"));
+
+    tagver_cmd()
+        .current_dir(repo_path)
+        .env("TAGVER_HEIGHTPLACEMENT", "build-metadata")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("1.0.1-alpha.0+1"));
+}
+
+#[test]
+fn test_metadata_preset() {
+    let temp = create_git_repo_with_tag("1.0.0");
+
+    tagver_cmd()
+        .current_dir(temp.path())
+        .env("GITHUB_RUN_NUMBER", "42")
+        .arg("--metadata-preset")
+        .arg("ci")
+        .assert()
+        .success()
+        .stdout(predicate::str::is_match(r"1\.0\.0\+42\.[0-9a-f]{7}").unwrap());
+}
+
+#[test]
+fn test_invalid_build_metadata_fails() {
+    let temp = create_git_repo_with_tag("1.0.0");
+
+    tagver_cmd()
+        .current_dir(temp.path())
+        .arg("--build-metadata")
+        .arg("feature/x y")
+        .assert()
+        .code(2);
+
+    tagver_cmd()
+        .current_dir(temp.path())
+        .arg("--build-metadata")
+        .arg("feature/x y")
+        .arg("--identifier-validation")
+        .arg("sanitize")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("1.0.0+feature-x-y"));
+}
+
+#[test]
+fn test_json_output_large_major() {
+    let temp = create_git_repo_with_tag("20240101.0.0");
+
+    tagver_cmd()
+        .current_dir(temp.path())
+        .arg("--format")
+        .arg("json")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(r#""major": 20240101"#));
+}
+
+#[test]
+fn test_revision() {
+    let temp = create_git_repo_with_tag("1.0.0");
+    let repo_path = temp.path();
+
+    StdCommand::new("git")
+        .args(["commit", "--allow-empty", "-m", "next"])
+        .current_dir(repo_path)
+        .assert()
+        .success();
+
+    tagver_cmd()
+        .current_dir(repo_path)
+        .arg("--revision")
+        .arg("HEAD~1")
+        .assert()
+        .success()
+        .stdout("1.0.0\n");
+
+    tagver_cmd()
+        .current_dir(repo_path)
+        .arg("--revision")
+        .arg("does-not-exist")
+        .assert()
+        .code(2);
+}
+
+#[test]
+fn test_height_exclusions() {
+    let temp = create_git_repo_with_tag("1.0.0");
+    let repo_path = temp.path();
+
+    for message in ["fix: a bug", "chore(deps): bump serde"] {
+        StdCommand::new("git")
+            .args(["commit", "--allow-empty", "-m", message])
+            .current_dir(repo_path)
+            .assert()
+            .success();
+    }
+
+    tagver_cmd()
+        .current_dir(repo_path)
+        .arg("--height-exclude-message")
+        .arg(r"^chore\(deps\)")
+        .assert()
+        .success()
+        .stdout("1.0.1-alpha.0.1\n");
+
+    tagver_cmd()
+        .current_dir(repo_path)
+        .env("TAGVER_HEIGHTEXCLUDEMESSAGE", "bump|bug")
+        .assert()
+        .success()
+        .stdout("1.0.0\n");
+}
+
+#[test]
+fn test_replace_refs() {
+    let temp = create_git_repo_with_tag("1.0.0");
+    let repo_path = temp.path();
+
+    StdCommand::new("git")
+        .args(["checkout", "--orphan", "rewritten"])
+        .current_dir(repo_path)
+        .assert()
+        .success();
+    StdCommand::new("git")
+        .args(["commit", "--allow-empty", "-m", "new root"])
+        .current_dir(repo_path)
+        .assert()
+        .success();
+    StdCommand::new("git")
+        .args(["replace", "--graft", "HEAD", "1.0.0"])
+        .current_dir(repo_path)
+        .assert()
+        .success();
+
+    tagver_cmd()
+        .current_dir(repo_path)
+        .assert()
+        .success()
+        .stdout("1.0.1-alpha.0.1\n");
+
+    tagver_cmd()
+        .current_dir(repo_path)
+        .arg("--no-replace-objects")
+        .assert()
+        .success()
+        .stdout("0.0.0-alpha.0\n");
+
+    tagver_cmd()
+        .current_dir(repo_path)
+        .env("GIT_NO_REPLACE_OBJECTS", "1")
+        .assert()
+        .success()
+        .stdout("0.0.0-alpha.0\n");
+}
+
+#[test]
+fn test_remote_tags() {
+    let remote = create_git_repo_with_tag("1.0.0");
+    let temp = TempDir::new().unwrap();
+    let clone = temp.path().join("clone");
+
+    StdCommand::new("git")
+        .args(["clone", "--no-tags"])
+        .arg(format!("file://{}", remote.path().display()))
+        .arg(&clone)
+        .assert()
+        .success();
+
+    tagver_cmd()
+        .current_dir(&clone)
+        .assert()
+        .success()
+        .stdout("0.0.0-alpha.0\n");
+
+    tagver_cmd()
+        .current_dir(&clone)
+        .arg("--remote-tags")
+        .assert()
+        .success()
+        .stdout("1.0.0\n");
+
+    tagver_cmd()
+        .current_dir(&clone)
+        .arg("--remote-tags=upstream")
+        .assert()
+        .code(1);
+}
+
+#[test]
+fn test_git_dir_and_work_tree_env_vars() {
+    let temp = create_git_repo_with_tag("1.2.3");
+    let elsewhere = TempDir::new().unwrap();
+
+    tagver_cmd()
+        .current_dir(elsewhere.path())
+        .env("GIT_DIR", temp.path().join(".git"))
+        .env("GIT_WORK_TREE", temp.path())
+        .assert()
+        .success()
+        .stdout("1.2.3\n");
+}
+
+#[test]
+fn test_ceiling_directories_env_var() {
+    let temp = create_git_repo_with_tag("1.2.3");
+    let nested = temp.path().join("nested");
+    std::fs::create_dir(&nested).unwrap();
+
+    tagver_cmd()
+        .current_dir(&nested)
+        .env("GIT_CEILING_DIRECTORIES", temp.path())
+        .assert()
+        .code(3);
+
+    // Ceilings that aren't parents of the directory are ignored
+    let unrelated = TempDir::new().unwrap();
+    tagver_cmd()
+        .current_dir(&nested)
+        .env("GIT_CEILING_DIRECTORIES", unrelated.path())
+        .assert()
+        .success()
+        .stdout("1.2.3\n");
+}
+
+#[test]
+fn test_repository_owned_by_other_user() {
+    let temp = create_git_repo_with_tag("1.2.3");
+    let repo_path = temp.path().canonicalize().unwrap();
+    let home = TempDir::new().unwrap();
+
+    // Changing ownership requires root; nothing to check otherwise
+    let chown = StdCommand::new("chown")
+        .args(["-R", "12345:12345"])
+        .arg(&repo_path)
+        .output();
+    if !chown.is_ok_and(|output| output.status.success()) {
+        return;
+    }
+
+    tagver_cmd()
+        .current_dir(&repo_path)
+        .env("HOME", home.path())
+        .env("GIT_CONFIG_NOSYSTEM", "1")
+        .env_remove("SUDO_UID")
+        .assert()
+        .code(3)
+        .stdout(predicate::str::contains("safe.directory"));
+
+    std::fs::write(
+        home.path().join(".gitconfig"),
+        format!("[safe]\n\tdirectory = {}\n", repo_path.display()),
+    )
+    .unwrap();
+
+    tagver_cmd()
+        .current_dir(&repo_path)
+        .env("HOME", home.path())
+        .env("GIT_CONFIG_NOSYSTEM", "1")
+        .env_remove("SUDO_UID")
+        .assert()
+        .success()
+        .stdout("1.2.3\n");
+}
+
+#[test]
+fn test_tags_subcommand() {
+    let temp = create_git_repo_with_tag("1.0.0");
+    let repo_path = temp.path();
+
+    StdCommand::new("git")
+        .args(["tag", "not-a-version"])
+        .current_dir(repo_path)
+        .assert()
+        .success();
+
+    tagver_cmd()
+        .current_dir(repo_path)
+        .arg("tags")
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::is_match(r"(?m)^1\.0\.0\s+[0-9a-f]{7}\s+yes\s+no\s+1\.0\.0$").unwrap(),
+        )
+        .stdout(predicate::str::contains(
+            "skipped: not a valid SemVer version",
+        ));
+
+    // Options apply after the subcommand too
+    tagver_cmd()
+        .arg("tags")
+        .arg(repo_path)
+        .arg("--format")
+        .arg("json")
+        .arg("--tag-prefix")
+        .arg("v")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "\"skipped\": \"no matching prefix or pattern\"",
+        ));
+}
+
+#[test]
+fn test_tag_requirement() {
+    let temp = create_git_repo_with_tag("1.0.0");
+    let repo_path = temp.path();
+
+    StdCommand::new("git")
+        .args(["tag", "-a", "0.9.0", "-m", "Release 0.9.0"])
+        .current_dir(repo_path)
+        .assert()
+        .success();
+
+    tagver_cmd()
+        .current_dir(repo_path)
+        .arg("--tag-requirement")
+        .arg("annotated")
+        .assert()
+        .success()
+        .stdout("0.9.0\n");
+
+    tagver_cmd()
+        .current_dir(repo_path)
+        .args(["tags", "--tag-requirement", "annotated"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("skipped: lightweight tag"));
+
+    // Verification needs an allowed signers file
+    tagver_cmd()
+        .current_dir(repo_path)
+        .arg("--tag-requirement")
+        .arg("verified")
+        .assert()
+        .code(2);
+}
+
+#[test]
+fn test_json_errors() {
+    let temp = TempDir::new().unwrap();
+    let output = tagver_cmd()
+        .current_dir(temp.path())
+        .args(["--format", "json"])
+        .assert()
+        .code(3)
+        .get_output()
+        .stdout
+        .clone();
+    let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(json["error"]["code"], "not_a_repository");
+    assert_eq!(json["error"]["exit_code"], 3);
+    assert!(json["error"]["message"]
+        .as_str()
+        .unwrap()
+        .contains("Git repository not found"));
+
+    let temp = create_git_repo_with_tag("1.0.0");
+    let output = tagver_cmd()
+        .current_dir(temp.path())
+        .args(["--format", "json", "--revision", "does-not-exist"])
+        .assert()
+        .code(2)
+        .get_output()
+        .stdout
+        .clone();
+    let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(json["error"]["code"], "invalid_config");
+
+    let output = tagver_cmd()
+        .current_dir(temp.path())
+        .args(["tags", "--format", "json", "--revision", "does-not-exist"])
+        .assert()
+        .code(2)
+        .get_output()
+        .stdout
+        .clone();
+    let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(json["error"]["code"], "invalid_config");
+
+    // Usage errors are reported as JSON too
+    for args in [
+        vec!["--format", "json", "--auto-increment", "huge"],
+        vec!["--no-such-option", "-f", "JSON"],
+        vec!["--format=json", "bump", "sideways", "1.0.0"],
+    ] {
+        let output = tagver_cmd()
+            .current_dir(temp.path())
+            .args(&args)
+            .assert()
+            .code(2)
+            .get_output()
+            .stdout
+            .clone();
+        let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+        assert_eq!(json["error"]["code"], "invalid_config", "{:?}", args);
+        assert_eq!(json["error"]["exit_code"], 2);
+    }
+    tagver_cmd()
+        .current_dir(temp.path())
+        .arg("--no-such-option")
+        .assert()
+        .code(2)
+        .stdout("");
+}
+
+#[test]
+fn test_strict() {
+    // No commits
+    let temp = TempDir::new().unwrap();
+    StdCommand::new("git")
+        .args(["init", "-q"])
+        .current_dir(temp.path())
+        .assert()
+        .success();
+    tagver_cmd()
+        .current_dir(temp.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::ends_with("0.0.0-alpha.0\n"));
+    tagver_cmd()
+        .current_dir(temp.path())
+        .args(["--strict", "--format", "json"])
+        .assert()
+        .code(4)
+        .stdout(predicate::str::contains("\"code\": \"no_commits\""));
+
+    // A shallow clone
+    let origin = create_git_repo_with_tag("1.0.0");
+    StdCommand::new("git")
+        .args(["commit", "--allow-empty", "-m", "Second commit"])
+        .current_dir(origin.path())
+        .assert()
+        .success();
+    let parent = TempDir::new().unwrap();
+    StdCommand::new("git")
+        .args(["clone", "-q", "--depth", "1"])
+        .arg(format!("file://{}", origin.path().display()))
+        .arg("clone")
+        .current_dir(parent.path())
+        .assert()
+        .success();
+    let clone = parent.path().join("clone");
+
+    tagver_cmd()
+        .current_dir(&clone)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Shallow repository detected"));
+    for backend in ["gix", "git"] {
+        tagver_cmd()
+            .current_dir(&clone)
+            .args(["--backend", backend])
+            .env("TAGVER_STRICT", "true")
+            .assert()
+            .code(5)
+            .stdout(predicate::str::contains(
+                "Shallow repository detected - version calculation may be incorrect",
+            ));
+    }
+}
+
+#[test]
+fn test_unresolved_tag_is_logged() {
+    let temp = create_git_repo_with_tag("1.0.0");
+    let repo_path = temp.path();
+
+    // A tag pointing to an object that doesn't exist
+    std::fs::write(
+        repo_path.join(".git/refs/tags/2.0.0"),
+        "0123456789abcdef0123456789abcdef01234567\n",
+    )
+    .unwrap();
+
+    tagver_cmd()
+        .current_dir(repo_path)
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("Ignoring tag '2.0.0': target can't be resolved")
+                .and(predicate::str::ends_with("1.0.0\n")),
+        );
+}
+
+#[test]
+fn test_directory_named_like_subcommand() {
+    let temp = TempDir::new().unwrap();
+    let repo = create_git_repo_with_tag("1.2.3");
+    let tags_dir = temp.path().join("tags");
+    std::fs::rename(repo.path(), &tags_dir).unwrap();
+
+    for args in [vec!["./tags"], vec!["--", "tags"]] {
+        tagver_cmd()
+            .current_dir(temp.path())
+            .args(&args)
+            .assert()
+            .success()
+            .stdout(predicate::str::ends_with("1.2.3\n"));
+    }
+
+    // The subcommand wins, with a hint
+    tagver_cmd()
+        .current_dir(temp.path())
+        .arg("tags")
+        .assert()
+        .code(3)
+        .stdout(predicate::str::contains(
+            "to version the directory 'tags', use 'tagver ./tags'",
+        ));
+}
+
+#[test]
+fn test_json_logs() {
+    let temp = create_git_repo_with_tag("1.0.0");
+    let repo_path = temp.path();
+
+    StdCommand::new("git")
+        .args(["tag", "not-a-version"])
+        .current_dir(repo_path)
+        .assert()
+        .success();
+    StdCommand::new("git")
+        .args(["commit", "--allow-empty", "-m", "Second commit"])
+        .current_dir(repo_path)
+        .assert()
+        .success();
+
+    let output = tagver_cmd()
+        .current_dir(repo_path)
+        .args(["--log-format", "json", "--verbosity", "debug"])
+        .assert()
+        .success()
+        .stdout("1.0.1-alpha.0.1\n")
+        .get_output()
+        .stderr
+        .clone();
+
+    let events: Vec<serde_json::Value> = String::from_utf8(output)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("Log line is not JSON"))
+        .collect();
+    let find = |message: &str| {
+        events
+            .iter()
+            .find(|event| event["message"].as_str().unwrap().contains(message))
+            .unwrap_or_else(|| panic!("No event '{}' in {:?}", message, events))
+    };
+
+    let skipped = find("Ignoring tag 'not-a-version'");
+    assert_eq!(skipped["level"], "DEBUG");
+    assert_eq!(skipped["fields"]["reason"], "not a valid SemVer version");
+
+    let base = find("as the base");
+    assert_eq!(base["fields"]["tag"], "1.0.0");
+    let walk = base["spans"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|span| span["name"] == "walk")
+        .expect("No walk span");
+    assert_eq!(walk["height"], 1);
+    assert!(walk["elapsed_ms"].is_u64());
+
+    let parsed = find("version tags on");
+    assert_eq!(parsed["fields"]["tags"], 1);
+}
+
+#[test]
+fn test_next() {
+    let temp = create_git_repo_with_tag("v1.2.4-rc.1");
+    let repo_path = temp.path();
+
+    StdCommand::new("git")
+        .args([
+            "commit",
+            "--allow-empty",
+            "-m",
+            "feat: preview next versions",
+        ])
+        .current_dir(repo_path)
+        .assert()
+        .success();
+
+    tagver_cmd()
+        .current_dir(repo_path)
+        .args(["next", "--tag-prefix", "v"])
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("base:         1.2.4-rc.1 (tag v1.2.4-rc.1, height 1)")
+                .and(predicate::str::contains("major:        2.0.0"))
+                .and(predicate::str::contains("minor:        1.3.0"))
+                .and(predicate::str::contains("patch:        1.2.4"))
+                .and(predicate::str::contains("prerelease:   1.2.4-rc.2"))
+                .and(predicate::str::contains("conventional: minor (1.3.0)")),
+        );
+
+    let output = tagver_cmd()
+        .current_dir(repo_path)
+        .args(["next", "--tag-prefix", "v", "--format", "json"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(json["base_tag"], "v1.2.4-rc.1");
+    assert_eq!(json["prerelease"], "1.2.4-rc.2");
+    assert_eq!(json["conventional"]["bump"], "minor");
+    assert_eq!(json["conventional"]["version"], "1.3.0");
+}
+
+#[test]
+fn test_bump() {
+    let temp = TempDir::new().unwrap();
+    let cases = [
+        (vec!["bump", "prerelease", "1.2.4-rc.1"], "1.2.4-rc.2"),
+        (
+            vec!["bump", "prerelease", "1.2.3", "-p", "beta.0"],
+            "1.2.4-beta.0",
+        ),
+        (vec!["bump", "promote", "1.0.0-alpha.3"], "1.0.0-beta.1"),
+        (vec!["bump", "promote", "1.0.0-rc.2"], "1.0.0"),
+        (
+            vec![
+                "bump",
+                "promote",
+                "1.0.0-preview.2",
+                "--pre-release-phases",
+                "preview,rc",
+            ],
+            "1.0.0-rc.1",
+        ),
+        (vec!["bump", "finalize", "1.0.0-rc.2+abc"], "1.0.0"),
+        (
+            vec!["bump", "minor", "v1.3.0-rc.1", "--tag-prefix", "v"],
+            "1.3.0",
+        ),
+        (vec!["bump", "major", "1.3.0"], "2.0.0"),
+    ];
+    for (args, expected) in cases {
+        // Bumping doesn't need a repository
+        tagver_cmd()
+            .current_dir(temp.path())
+            .args(&args)
+            .assert()
+            .success()
+            .stdout(format!("{}\n", expected));
+    }
+
+    tagver_cmd()
+        .current_dir(temp.path())
+        .args(["bump", "promote", "1.0.0", "--format", "json"])
+        .assert()
+        .code(2)
+        .stdout(predicate::str::contains("\"code\": \"invalid_config\""));
+
+    for version in ["not-a-version", "1.0"] {
+        tagver_cmd()
+            .current_dir(temp.path())
+            .args(["bump", "patch", version, "--format", "json"])
+            .assert()
+            .code(2)
+            .stdout(predicate::str::contains("\"code\": \"invalid_config\""))
+            .stdout(predicate::str::contains(format!(
+                "Invalid version: '{}'",
+                version
+            )));
+    }
+}
+
+#[test]
+fn test_check() {
+    let temp = create_git_repo_with_tag("2.1.0");
+    let repo_path = temp.path();
+
+    tagver_cmd()
+        .current_dir(repo_path)
+        .args(["check", "--require", ">=2.0.0, <3", "--require-stable"])
+        .assert()
+        .success()
+        .stdout(predicate::str::ends_with("2.1.0\n"));
+
+    tagver_cmd()
+        .current_dir(repo_path)
+        .args(["check", "--require", ">=3"])
+        .assert()
+        .code(8)
+        .stdout(predicate::str::contains("2.1.0 does not satisfy '>=3'"));
+
+    StdCommand::new("git")
+        .args(["commit", "--allow-empty", "-m", "Another commit"])
+        .current_dir(repo_path)
+        .assert()
+        .success();
+
+    tagver_cmd()
+        .current_dir(repo_path)
+        .args(["check", "--require-stable", "--format", "json"])
+        .assert()
+        .code(8)
+        .stdout(predicate::str::contains(
+            "\"code\": \"requirement_not_met\"",
+        ))
+        .stdout(predicate::str::contains(
+            "2.1.1-alpha.0.1 is a pre-release, but a stable release is required",
+        ));
+
+    tagver_cmd()
+        .current_dir(repo_path)
+        .args(["check", "--require", ">=2.0.0, <3"])
+        .assert()
+        .code(8);
+
+    tagver_cmd()
+        .current_dir(repo_path)
+        .args(["check", "--require", ">=2.0.0, <3", "--include-prerelease"])
+        .assert()
+        .success();
+
+    // A requirement is needed, and must parse
+    tagver_cmd()
+        .current_dir(repo_path)
+        .args(["check"])
+        .assert()
+        .code(2);
+    tagver_cmd()
+        .current_dir(repo_path)
+        .args(["check", "--require", "two"])
+        .assert()
+        .code(2);
+}
+
+#[test]
+fn test_tag_selection() {
+    let temp = create_git_repo_with_tag("1.1.0-rc.1");
+    let repo_path = temp.path();
+
+    StdCommand::new("git")
+        .args(["tag", "1.0.0"])
+        .current_dir(repo_path)
+        .assert()
+        .success();
+
+    tagver_cmd()
+        .current_dir(repo_path)
+        .args(["--verbosity", "verbose"])
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("Using tag '1.1.0-rc.1' over '1.0.0' on the same commit")
+                .and(predicate::str::ends_with("\n1.1.0-rc.1\n")),
+        );
+
+    tagver_cmd()
+        .current_dir(repo_path)
+        .env("TAGVER_TAGSELECTION", "prefer-rtm")
+        .assert()
+        .success()
+        .stdout("1.0.0\n");
+}
+
+#[test]
+fn test_minver_env_vars() {
+    let temp = create_git_repo();
+    let repo_path = temp.path();
+
+    StdCommand::new("git")
+        .args(["tag", "v1.0.0"])
+        .current_dir(repo_path)
+        .assert()
+        .success();
+    StdCommand::new("git")
+        .args(["commit", "--allow-empty", "-m", "feat: new feature"])
+        .current_dir(repo_path)
+        .assert()
+        .success();
+
+    tagver_cmd()
+        .current_dir(repo_path)
+        .env("MINVERTAGPREFIX", "v")
+        .env("MINVERDEFAULTPRERELEASEPHASE", "preview")
+        .env("MINVERIGNOREHEIGHT", "False")
+        .assert()
+        .success()
+        .stdout("1.0.1-preview.0.1\n");
+
+    // TAGVER_* variables and flags win over MinVer's
+    tagver_cmd()
+        .current_dir(repo_path)
+        .env("MINVERTAGPREFIX", "v")
+        .env("MINVERDEFAULTPRERELEASEPHASE", "preview")
+        .env("TAGVER_DEFAULTPRERELEASEIDENTIFIERS", "beta.0")
+        .assert()
+        .success()
+        .stdout("1.0.1-beta.0.1\n");
+
+    tagver_cmd()
+        .current_dir(repo_path)
+        .env("MINVERTAGPREFIX", "v")
+        .env("MINVERDEFAULTPRERELEASEPHASE", "preview")
+        .arg("--default-pre-release-phase")
+        .arg("rc")
+        .assert()
+        .success()
+        .stdout("1.0.1-rc.0.1\n");
+}
+
+#[test]
+fn test_minver_compat() {
+    let temp = create_git_repo_with_tag("1.0.0");
+    let repo_path = temp.path();
+
+    tagver_cmd()
+        .current_dir(repo_path)
+        .args(["--minver-compat", "--format", "json", "-v", "info"])
+        .assert()
+        .success()
+        .stdout("1.0.0\n")
+        .stderr("MinVer: Calculated version 1.0.0.\n");
+
+    // Not a working directory: the default version and a warning, not an error
+    let empty = TempDir::new().unwrap();
+    tagver_cmd()
+        .current_dir(empty.path())
+        .env("GIT_CEILING_DIRECTORIES", empty.path().parent().unwrap())
+        .env("TAGVER_MINVERCOMPAT", "true")
+        .assert()
+        .success()
+        .stdout("0.0.0-alpha.0\n")
+        .stderr(predicate::str::contains(
+            "MinVer: warning MINVER1001: '.' is not a valid Git working directory. Using default version 0.0.0-alpha.0.",
+        ));
+
+    tagver_cmd()
+        .arg("--minver-compat")
+        .arg(empty.path().join("missing"))
+        .assert()
+        .code(2)
+        .stdout("")
+        .stderr(predicate::str::contains("MinVer: error MINVER1002:"));
+}
@@ -1,27 +1,95 @@
-use criterion::{black_box, criterion_group, criterion_main, Criterion};
-use tagver::{calculate_version, CalculationResult, Config, Version};
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use tagver::{calculate_version, Config};
+use tempfile::TempDir;
+
+/// Number of commits in the generated repository, overridable for larger runs.
+fn commit_count() -> usize {
+    std::env::var("TAGVER_BENCH_COMMITS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(50_000)
+}
+
+fn git(args: &[&str], cwd: &Path) {
+    let status = Command::new("git")
+        .args(args)
+        .current_dir(cwd)
+        .status()
+        .expect("Failed to run git");
+    assert!(status.success(), "git {:?} failed", args);
+}
+
+/// Generate a linear history with a single tag on the root commit, so the walk has to
+/// traverse the whole repository.
+fn generate_repository(commits: usize) -> TempDir {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let path = temp_dir.path();
+
+    git(&["init", "--quiet", "--initial-branch=main"], path);
+
+    let mut stream = String::new();
+    for i in 1..=commits {
+        stream.push_str("commit refs/heads/main\n");
+        stream.push_str(&format!("mark :{}\n", i));
+        stream.push_str(&format!(
+            "committer Bench <bench@example.com> {} +0000\n",
+            1_700_000_000 + i
+        ));
+        stream.push_str("data 1\n.\n");
+        if i > 1 {
+            stream.push_str(&format!("from :{}\n", i - 1));
+        }
+        stream.push('\n');
+    }
+    stream.push_str("reset refs/tags/1.0.0\nfrom :1\n\n");
+
+    let mut import = Command::new("git")
+        .args(["fast-import", "--quiet"])
+        .current_dir(path)
+        .stdin(Stdio::piped())
+        .spawn()
+        .expect("Failed to run git fast-import");
+    import
+        .stdin
+        .take()
+        .expect("Failed to open stdin")
+        .write_all(stream.as_bytes())
+        .expect("Failed to write fast-import stream");
+    assert!(import.wait().expect("git fast-import failed").success());
+
+    git(&["checkout", "--quiet", "main"], path);
+    temp_dir
+}
 
 fn benchmark_version_calculation(c: &mut Criterion) {
-    c.bench_function("version_calculation_no_tags", |b| {
-        b.iter(|| {
-            let config = Config::default();
-            let work_dir = ".";
-            let _result = calculate_version(work_dir, &config);
-        })
-    });
-
-    c.bench_function("version_calculation_with_mock", |b| {
-        b.iter(|| {
-            // This is a mock benchmark - in a real scenario you'd have a test repo
-            let result = CalculationResult {
-                version: Version::new(1, 0, 0),
-                height: 0,
-                is_from_tag: false,
-                work_dir: ".".into(),
-            };
-            black_box(result);
-        })
-    });
+    let commits = commit_count();
+    let repo = generate_repository(commits);
+    let path = repo.path();
+    let config = Config::default();
+
+    let mut group = c.benchmark_group("version_calculation");
+    group.sample_size(10);
+
+    git(&["config", "core.commitGraph", "false"], path);
+    group.bench_with_input(
+        BenchmarkId::new("object_lookup", commits),
+        &path,
+        |b, path| b.iter(|| calculate_version(*path, &config).expect("calculation failed")),
+    );
+
+    git(&["config", "core.commitGraph", "true"], path);
+    git(&["commit-graph", "write", "--reachable"], path);
+    group.bench_with_input(
+        BenchmarkId::new("commit_graph", commits),
+        &path,
+        |b, path| b.iter(|| calculate_version(*path, &config).expect("calculation failed")),
+    );
+
+    group.finish();
 }
 
 criterion_group!(benches, benchmark_version_calculation);
@@ -0,0 +1,252 @@
+//! Persistent result cache for repeated calculations on an unchanged repository.
+//!
+//! The cache is a single entry in `<git-dir>/tagver-cache`, keyed by the HEAD commit and
+//! reference, a digest of every tag and replace ref and the shallow boundary, and the
+//! [`Config`] fields that affect the result, including the contents of the allowed signers
+//! file when tags are verified.
+//! Any commit, checkout, tag creation, replacement, fetch that moves a tag or deepens a
+//! shallow clone changes the key, so a stale entry is never returned.
+//! Cache failures are never fatal; the version is calculated as usual.
+
+use std::path::{Path, PathBuf};
+
+use crate::config::{Config, TagRequirement};
+use crate::error::Result;
+use crate::git::{self, Repository};
+use crate::metadata::CI_BUILD_VARIABLES;
+use crate::signing;
+use crate::version::Version;
+
+/// File name of the cache inside the Git directory.
+pub const CACHE_FILE: &str = "tagver-cache";
+
+const FORMAT: &str = "tagver-cache-v2";
+
+/// Calculate the version, reusing the cached result when nothing relevant changed.
+pub fn calculate_version(repo: &Repository, config: &Config) -> Result<(Version, u32, bool)> {
+    let Some(key) = cache_key(repo, config) else {
+        return git::calculate_version(repo, config);
+    };
+    let path = cache_path(repo);
+
+    if let Some(result) = read(&path, &key) {
+        tracing::debug!("Using cached version from {}", path.display());
+        return Ok(result);
+    }
+
+    let result = git::calculate_version(repo, config)?;
+    if let Err(e) = write(&path, &key, &result) {
+        tracing::debug!("Failed to write cache {}: {}", path.display(), e);
+    }
+
+    Ok(result)
+}
+
+/// Path of the cache file for `repo`.
+pub fn cache_path(repo: &Repository) -> PathBuf {
+    repo.inner().git_dir().join(CACHE_FILE)
+}
+
+/// Build the cache key, or `None` when the result can't be cached.
+fn cache_key(repo: &Repository, config: &Config) -> Option<String> {
+    // The working tree state isn't part of the key
+    if config
+        .build_metadata
+        .as_deref()
+        .is_some_and(|template| template.contains("{dirty}"))
+    {
+        return None;
+    }
+
+    let inner = repo.inner();
+    let head_id = git::target_commit(inner, config).ok()??;
+    let head_name = inner
+        .head_name()
+        .ok()?
+        .map(|name| name.as_bstr().to_string());
+
+    let mut tags = Digest::new();
+    for tag in inner.references().ok()?.tags().ok()? {
+        let tag = tag.ok()?;
+        tags.field(tag.name().as_bstr());
+        tags.optional(tag.target().try_id().map(|id| id.as_bytes()));
+    }
+    let mut replacements: Vec<_> = git::replace_refs(inner, config).into_iter().collect();
+    replacements.sort();
+    for (original, replacement) in &replacements {
+        tags.field(original.as_bytes());
+        tags.field(replacement.as_bytes());
+    }
+    // Deepening a shallow clone changes the history, but not HEAD or the tags
+    tags.optional(std::fs::read(inner.shallow_file()).ok());
+
+    let mut settings = Digest::new();
+    hash_config(config, &mut settings);
+    for name in CI_BUILD_VARIABLES {
+        settings.optional(std::env::var(name).ok());
+    }
+    if config.tag_requirement == TagRequirement::Verified {
+        settings.optional(
+            signing::allowed_signers_path(inner, config).and_then(|path| std::fs::read(path).ok()),
+        );
+    }
+
+    Some(format!(
+        "{} {} {} {}",
+        head_id,
+        head_name.unwrap_or_default(),
+        tags.finish()?,
+        settings.finish()?
+    ))
+}
+
+/// Add the config fields that affect the result. The pattern lists every field, so a new
+/// one doesn't compile until it is either hashed or ignored here.
+fn hash_config(config: &Config, digest: &mut Digest) {
+    #[rustfmt::skip] // rustfmt misindents the cfg'd field
+    let Config {
+        work_dir: _,
+        tag_prefix,
+        tag_patterns,
+        exclude_tag_patterns,
+        tag_requirement,
+        tag_selection,
+        allowed_signers,
+        auto_increment,
+        minimum_major_minor,
+        default_prerelease_identifiers,
+        prerelease_phases: _,
+        build_metadata,
+        ignore_height,
+        height_metric,
+        height_exclude_messages,
+        height_exclude_authors,
+        height_format,
+        identifier_validation,
+        strict: _,
+        revision,
+        use_superproject: _,
+        use_replace_refs,
+        cache: _,
+        #[cfg(feature = "remote-tags")]
+        remote_tags: _,
+        backend: _,
+        verbosity: _,
+    } = config;
+
+    digest.field(tag_prefix);
+    for patterns in [tag_patterns, exclude_tag_patterns, height_exclude_authors] {
+        digest.field(patterns.len().to_string());
+        for pattern in patterns {
+            digest.field(pattern.to_string());
+        }
+    }
+    digest.field(format!("{:?}", tag_requirement));
+    digest.field(format!("{:?}", tag_selection));
+    digest.optional(
+        allowed_signers
+            .as_ref()
+            .map(|path| path.to_string_lossy().into_owned()),
+    );
+    digest.field(format!("{:?}", auto_increment));
+    digest.optional(
+        minimum_major_minor
+            .as_ref()
+            .map(|mm| format!("{}.{}", mm.major, mm.minor)),
+    );
+    digest.field(default_prerelease_identifiers.join("."));
+    digest.optional(build_metadata.as_ref());
+    digest.field(ignore_height.to_string());
+    digest.field(format!("{:?}", height_metric));
+    digest.field(height_exclude_messages.len().to_string());
+    for regex in height_exclude_messages {
+        digest.field(regex.as_str());
+    }
+    digest.field(format!("{:?}", height_format.placement));
+    digest.field(format!("{:?}", height_format.source));
+    digest.field(height_format.padding.to_string());
+    digest.optional(height_format.identifier.as_ref());
+    digest.field(format!("{:?}", identifier_validation));
+    digest.optional(revision.as_ref());
+    digest.field(use_replace_refs.to_string());
+}
+
+/// SHA-1 digest of the key's inputs; unlike `std`'s hashers it is stable across Rust
+/// releases, so a cache written by one tagver build is read by another.
+struct Digest(gix::hash::Hasher);
+
+impl Digest {
+    fn new() -> Self {
+        Self(gix::hash::hasher(gix::hash::Kind::Sha1))
+    }
+
+    /// Add a value, length-prefixed so adjacent values can't run into each other.
+    fn field(&mut self, value: impl AsRef<[u8]>) {
+        let bytes = value.as_ref();
+        self.0.update(&(bytes.len() as u64).to_le_bytes());
+        self.0.update(bytes);
+    }
+
+    /// Add a value that may be missing, distinguishing a missing value from an empty one.
+    fn optional(&mut self, value: Option<impl AsRef<[u8]>>) {
+        match value {
+            Some(value) => {
+                self.0.update(&[1]);
+                self.field(value);
+            }
+            None => self.0.update(&[0]),
+        }
+    }
+
+    fn finish(self) -> Option<gix::ObjectId> {
+        self.0.try_finalize().ok()
+    }
+}
+
+fn read(path: &Path, key: &str) -> Option<(Version, u32, bool)> {
+    let contents = std::fs::read_to_string(path).ok()?;
+    let mut lines = contents.lines();
+
+    if lines.next()? != FORMAT || lines.next()? != key {
+        return None;
+    }
+
+    let mut version = Version::new(
+        lines.next()?.parse().ok()?,
+        lines.next()?.parse().ok()?,
+        lines.next()?.parse().ok()?,
+    );
+    let prerelease = lines.next()?;
+    if !prerelease.is_empty() {
+        version.prerelease = prerelease.split('.').map(|s| s.to_string()).collect();
+    }
+    let build_metadata = lines.next()?;
+    if !build_metadata.is_empty() {
+        version.build_metadata = Some(build_metadata.to_string());
+    }
+    let height = lines.next()?.parse().ok()?;
+    let is_from_tag = lines.next()?.parse().ok()?;
+
+    Some((version, height, is_from_tag))
+}
+
+fn write(path: &Path, key: &str, result: &(Version, u32, bool)) -> std::io::Result<()> {
+    let (version, height, is_from_tag) = result;
+    let contents = [
+        FORMAT.to_string(),
+        key.to_string(),
+        version.major.to_string(),
+        version.minor.to_string(),
+        version.patch.to_string(),
+        version.prerelease.join("."),
+        version.build_metadata.clone().unwrap_or_default(),
+        height.to_string(),
+        is_from_tag.to_string(),
+    ]
+    .join("\n");
+
+    // Write then rename, so concurrent readers never see a partial entry
+    let temp = path.with_extension(format!("tmp-{}", std::process::id()));
+    std::fs::write(&temp, contents + "\n")?;
+    std::fs::rename(&temp, path)
+}
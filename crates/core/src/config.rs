@@ -3,6 +3,10 @@
 use std::path::PathBuf;
 use std::str::FromStr;
 
+use regex::Regex;
+
+use crate::error::TagVerError;
+
 /// Verbosity levels for logging.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Verbosity {
@@ -28,6 +32,20 @@ impl FromStr for Verbosity {
     }
 }
 
+impl Verbosity {
+    /// The most verbose tracing level to show. The library only emits events and spans, so
+    /// this is for the subscriber an application installs.
+    pub fn level(&self) -> tracing::Level {
+        match self {
+            Verbosity::Quiet => tracing::Level::ERROR,
+            Verbosity::Normal => tracing::Level::WARN,
+            Verbosity::Verbose => tracing::Level::INFO,
+            Verbosity::Debug => tracing::Level::DEBUG,
+            Verbosity::Trace => tracing::Level::TRACE,
+        }
+    }
+}
+
 /// Version parts that can be auto-incremented.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum VersionPart {
@@ -49,11 +67,233 @@ impl FromStr for VersionPart {
     }
 }
 
+/// Where the height is placed in a synthesized version.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum HeightPlacement {
+    /// Last prerelease identifier, e.g. `1.0.1-alpha.0.5`.
+    #[default]
+    Prerelease,
+    /// Build metadata, e.g. `1.0.1-alpha.0+5`.
+    BuildMetadata,
+}
+
+impl FromStr for HeightPlacement {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "prerelease" | "pre-release" => Ok(HeightPlacement::Prerelease),
+            "build-metadata" | "buildmetadata" | "build" => Ok(HeightPlacement::BuildMetadata),
+            _ => Err(format!("Invalid height placement: {}", s)),
+        }
+    }
+}
+
+/// The value written in place of the height.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum HeightSource {
+    /// Number of commits since the base tag.
+    #[default]
+    Height,
+    /// HEAD commit time in UTC, as `YYYYMMDDHHMMSS`.
+    Timestamp,
+    /// Total number of commits reachable from HEAD.
+    CommitCount,
+}
+
+impl FromStr for HeightSource {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "height" => Ok(HeightSource::Height),
+            "timestamp" => Ok(HeightSource::Timestamp),
+            "commit-count" | "commitcount" => Ok(HeightSource::CommitCount),
+            _ => Err(format!("Invalid height source: {}", s)),
+        }
+    }
+}
+
+/// How the height, the distance from the base tag to HEAD, is measured.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HeightMetric {
+    /// Steps along the first-parent chain to the nearest tag on it.
+    #[default]
+    FirstParent,
+    /// MinVer's walk: the highest version tagged on any parent path, at the distance it's
+    /// first reached when following first parents first.
+    MinVer,
+    /// Commits reachable from HEAD but not from the base tag, like
+    /// `git rev-list --count <tag>..HEAD`.
+    Ancestry,
+    /// Like [`HeightMetric::Ancestry`], without merge commits.
+    NoMerges,
+}
+
+impl FromStr for HeightMetric {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "first-parent" | "firstparent" => Ok(HeightMetric::FirstParent),
+            "minver" => Ok(HeightMetric::MinVer),
+            "ancestry" => Ok(HeightMetric::Ancestry),
+            "no-merges" | "nomerges" => Ok(HeightMetric::NoMerges),
+            _ => Err(format!("Invalid height metric: {}", s)),
+        }
+    }
+}
+
+/// How the height is rendered into a synthesized version.
+///
+/// The height is only written when the version is not exactly on a tag.
+///
+/// # Examples
+/// ```rust
+/// use tagver::config::HeightFormat;
+///
+/// let format = HeightFormat {
+///     padding: 4,
+///     identifier: Some("dev".into()),
+///     ..Default::default()
+/// };
+/// assert_eq!(format.identifiers(10), vec!["dev", "0010"]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct HeightFormat {
+    pub placement: HeightPlacement,
+    pub source: HeightSource,
+    /// Minimum number of digits, zero-padded on the left.
+    ///
+    /// Padded numbers are not valid SemVer prerelease identifiers, so with the default
+    /// [`IdentifierValidation::Error`] padding needs [`HeightPlacement::BuildMetadata`];
+    /// see [`Config::validate`].
+    pub padding: usize,
+    /// Identifier written before the height value.
+    pub identifier: Option<String>,
+}
+
+impl HeightFormat {
+    /// Render a height value into its version identifiers.
+    pub fn identifiers(&self, value: u64) -> Vec<String> {
+        let mut identifiers: Vec<String> = self.identifier.iter().cloned().collect();
+        identifiers.push(format!("{:0width$}", value, width = self.padding));
+        identifiers
+    }
+}
+
+/// What to do when a synthesized version has identifiers that are not valid SemVer.
+///
+/// Identifiers must be non-empty ASCII alphanumerics and hyphens, and numeric prerelease
+/// identifiers must not have leading zeros.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum IdentifierValidation {
+    /// Fail the calculation, so output always round-trips through the `semver` crate.
+    #[default]
+    Error,
+    /// Log a warning and emit the identifiers unchanged.
+    Warn,
+    /// Rewrite invalid identifiers: invalid characters become `-`, empty identifiers are
+    /// dropped and leading zeros are stripped from numeric prerelease identifiers.
+    Sanitize,
+}
+
+impl FromStr for IdentifierValidation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "error" => Ok(IdentifierValidation::Error),
+            "warn" => Ok(IdentifierValidation::Warn),
+            "sanitize" => Ok(IdentifierValidation::Sanitize),
+            _ => Err(format!("Invalid identifier validation: {}", s)),
+        }
+    }
+}
+
+/// Where the commit graph and tags are read from; see [`graph`](crate::graph).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Backend {
+    /// Read the repository with gix.
+    #[default]
+    Gix,
+    /// Run the `git` executable, for repository formats gix can't read. Only the version
+    /// calculation uses it; the cache, remote tags and superprojects need gix.
+    Git,
+}
+
+impl FromStr for Backend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "gix" => Ok(Backend::Gix),
+            "git" => Ok(Backend::Git),
+            _ => Err(format!("Invalid backend: {}", s)),
+        }
+    }
+}
+
+/// Which tag wins when a commit has several version tags.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TagSelection {
+    /// The highest SemVer precedence, so `1.1.0` over `1.1.0-rc.1`.
+    #[default]
+    Precedence,
+    /// A release tag over any pre-release tag, then by precedence.
+    PreferRtm,
+    /// The annotated tag with the newest tagger date, then by precedence. Lightweight tags
+    /// have no date and lose to annotated ones.
+    Newest,
+}
+
+impl FromStr for TagSelection {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "precedence" => Ok(TagSelection::Precedence),
+            "prefer-rtm" | "rtm" => Ok(TagSelection::PreferRtm),
+            "newest" => Ok(TagSelection::Newest),
+            _ => Err(format!("Invalid tag selection: {}", s)),
+        }
+    }
+}
+
+/// Which kinds of tags may serve as version tags.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum TagRequirement {
+    /// Lightweight and annotated tags.
+    #[default]
+    Any,
+    /// Annotated tags only.
+    Annotated,
+    /// Annotated tags carrying a GPG, SSH or X.509 signature, without checking it.
+    Signed,
+    /// Tags with an SSH signature from a key in the allowed signers file;
+    /// see [`signing`](crate::signing).
+    Verified,
+}
+
+impl FromStr for TagRequirement {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "any" => Ok(TagRequirement::Any),
+            "annotated" => Ok(TagRequirement::Annotated),
+            "signed" => Ok(TagRequirement::Signed),
+            "verified" => Ok(TagRequirement::Verified),
+            _ => Err(format!("Invalid tag requirement: {}", s)),
+        }
+    }
+}
+
 /// Major.minor constraint.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct MajorMinor {
-    pub major: u32,
-    pub minor: u32,
+    pub major: u64,
+    pub minor: u64,
 }
 
 impl MajorMinor {
@@ -65,24 +305,155 @@ impl MajorMinor {
         }
 
         let major = parts[0]
-            .parse::<u32>()
+            .parse::<u64>()
             .map_err(|_| format!("Invalid major version: {}", parts[0]))?;
         let minor = parts[1]
-            .parse::<u32>()
+            .parse::<u64>()
             .map_err(|_| format!("Invalid minor version: {}", parts[1]))?;
 
         Ok(MajorMinor { major, minor })
     }
 }
 
+/// A pattern selecting version tags and extracting the version text from the tag name.
+///
+/// Patterns are written as `prefix:<text>`, `regex:<expr>` or `glob:<pattern>`. Without a
+/// scheme, an expression containing a named `version` group is read as a regex and anything
+/// else as a glob.
+///
+/// - Prefix: the tag must start with the text; the remainder is the version.
+/// - Regex: searched in the tag name; the `version` group (or the whole match) is the version.
+/// - Glob: must match the whole tag name; `?` matches one character, `*` any run of
+///   characters, and the first `*` captures the version.
+///
+/// # Examples
+/// ```rust
+/// use tagver::config::TagPattern;
+///
+/// let glob: TagPattern = "myapp@*".parse().unwrap();
+/// assert_eq!(glob.extract_version("myapp@1.2.3"), Some("1.2.3"));
+///
+/// let regex: TagPattern = r"^release-(?<version>.+?)(-linux)?$".parse().unwrap();
+/// assert_eq!(regex.extract_version("release-1.2.3-linux"), Some("1.2.3"));
+/// assert_eq!(regex.extract_version("v1.2.3"), None);
+/// ```
+#[derive(Debug, Clone)]
+pub enum TagPattern {
+    Prefix(String),
+    Regex(Regex),
+    Glob { pattern: String, regex: Regex },
+}
+
+impl TagPattern {
+    /// Create a pattern from a regular expression.
+    pub fn regex(expr: &str) -> Result<Self, String> {
+        Regex::new(expr)
+            .map(TagPattern::Regex)
+            .map_err(|e| format!("Invalid tag pattern regex '{}': {}", expr, e))
+    }
+
+    /// Create a pattern from a glob, where the first `*` captures the version.
+    pub fn glob(pattern: &str) -> Result<Self, String> {
+        if pattern.is_empty() {
+            return Err("Tag pattern glob must not be empty".to_string());
+        }
+
+        let mut expr = String::from("^");
+        let mut captured = false;
+        for c in pattern.chars() {
+            match c {
+                '*' if !captured => {
+                    expr.push_str("(?<version>.*)");
+                    captured = true;
+                }
+                '*' => expr.push_str(".*"),
+                '?' => expr.push('.'),
+                c => expr.push_str(&regex::escape(&c.to_string())),
+            }
+        }
+        expr.push('$');
+
+        let regex = Regex::new(&expr)
+            .map_err(|e| format!("Invalid tag pattern glob '{}': {}", pattern, e))?;
+        Ok(TagPattern::Glob {
+            pattern: pattern.to_string(),
+            regex,
+        })
+    }
+
+    /// Return the version text of `tag_name` if the tag matches this pattern.
+    pub fn extract_version<'a>(&self, tag_name: &'a str) -> Option<&'a str> {
+        match self {
+            TagPattern::Prefix(prefix) => tag_name.strip_prefix(prefix.as_str()),
+            TagPattern::Regex(regex) | TagPattern::Glob { regex, .. } => {
+                let captures = regex.captures(tag_name)?;
+                captures
+                    .name("version")
+                    .or_else(|| captures.get(0))
+                    .map(|m| m.as_str())
+            }
+        }
+    }
+
+    /// Check whether `tag_name` matches this pattern.
+    pub fn matches(&self, tag_name: &str) -> bool {
+        self.extract_version(tag_name).is_some()
+    }
+}
+
+impl FromStr for TagPattern {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(prefix) = s.strip_prefix("prefix:") {
+            Ok(TagPattern::Prefix(prefix.to_string()))
+        } else if let Some(expr) = s.strip_prefix("regex:") {
+            TagPattern::regex(expr)
+        } else if let Some(pattern) = s.strip_prefix("glob:") {
+            TagPattern::glob(pattern)
+        } else if s.contains("(?<version>") || s.contains("(?P<version>") {
+            TagPattern::regex(s)
+        } else {
+            TagPattern::glob(s)
+        }
+    }
+}
+
+impl std::fmt::Display for TagPattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TagPattern::Prefix(prefix) => write!(f, "prefix:{}", prefix),
+            TagPattern::Regex(regex) => write!(f, "regex:{}", regex.as_str()),
+            TagPattern::Glob { pattern, .. } => write!(f, "glob:{}", pattern),
+        }
+    }
+}
+
 /// Runtime configuration for TagVer operations.
 ///
 /// # Defaults
 /// - `work_dir`: current directory (`.`)
 /// - `tag_prefix`: empty (accept all tags)
+/// - `tag_patterns`: empty (use `tag_prefix`)
+/// - `exclude_tag_patterns`: empty
+/// - `tag_requirement`: [`TagRequirement::Any`](crate::config::TagRequirement)
+/// - `tag_selection`: [`TagSelection::Precedence`](crate::config::TagSelection)
+/// - `allowed_signers`: `None` (git's `gpg.ssh.allowedSignersFile`)
 /// - `auto_increment`: [`VersionPart::Patch`](crate::config::VersionPart)
 /// - `default_prerelease_identifiers`: `"alpha.0"`
+/// - `prerelease_phases`: `alpha`, `beta`, `rc`
 /// - `ignore_height`: `false`
+/// - `height_metric`: [`HeightMetric::FirstParent`](crate::config::HeightMetric)
+/// - `height_exclude_messages`, `height_exclude_authors`: empty (every commit counts)
+/// - `height_format`: height as the last prerelease identifier, unpadded
+/// - `identifier_validation`: [`IdentifierValidation::Error`](crate::config::IdentifierValidation)
+/// - `strict`: `false`
+/// - `revision`: `None` (HEAD)
+/// - `use_superproject`: `false`
+/// - `use_replace_refs`: `true`
+/// - `cache`: `false`
+/// - `remote_tags`: `None` (local tags only; with the `remote-tags` feature)
+/// - `backend`: [`Backend::Gix`](crate::config::Backend)
 /// - `verbosity`: [`Verbosity::Normal`](crate::config::Verbosity)
 ///
 /// # Examples
@@ -102,11 +473,53 @@ impl MajorMinor {
 pub struct Config {
     pub work_dir: PathBuf,
     pub tag_prefix: String,
+    /// Patterns tried in order to select version tags; overrides `tag_prefix` when non-empty.
+    pub tag_patterns: Vec<TagPattern>,
+    /// Tags matching any of these patterns are never considered.
+    pub exclude_tag_patterns: Vec<TagPattern>,
+    pub tag_requirement: TagRequirement,
+    /// Which tag to use when a commit has several; the others are logged at verbose level.
+    pub tag_selection: TagSelection,
+    /// SSH allowed signers file for [`TagRequirement::Verified`].
+    pub allowed_signers: Option<PathBuf>,
     pub auto_increment: VersionPart,
     pub minimum_major_minor: Option<MajorMinor>,
     pub default_prerelease_identifiers: Vec<String>,
+    /// Pre-release phases in order, for [`Version::promote`](crate::Version::promote).
+    pub prerelease_phases: Vec<String>,
+    /// Build metadata, optionally a template with placeholders such as `{sha7}`;
+    /// see [`metadata`](crate::metadata).
     pub build_metadata: Option<String>,
     pub ignore_height: bool,
+    pub height_metric: HeightMetric,
+    /// Commits whose message matches any of these regexes don't count toward the height,
+    /// e.g. `^chore\(deps\)` or `\[skip version\]`. When HEAD is such a commit, the version
+    /// is that of its parent.
+    pub height_exclude_messages: Vec<Regex>,
+    /// Commits whose author email matches any of these patterns don't count toward the height.
+    pub height_exclude_authors: Vec<TagPattern>,
+    pub height_format: HeightFormat,
+    pub identifier_validation: IdentifierValidation,
+    /// Fail with [`TagVerError::NoCommits`] when the target has no commits, and with
+    /// [`TagVerError::ShallowRepo`] for a shallow clone, instead of returning the default
+    /// version or warning.
+    pub strict: bool,
+    /// Commit to calculate the version for instead of HEAD, e.g. a branch name or commit id.
+    /// Required for bare mirrors whose HEAD doesn't point at the wanted branch.
+    pub revision: Option<String>,
+    /// When run inside a submodule, calculate the version of the outermost superproject.
+    pub use_superproject: bool,
+    /// Follow replace refs (`git replace`, including grafts) when walking history, like git.
+    /// Also off when `GIT_NO_REPLACE_OBJECTS` is set or `core.useReplaceRefs` is false.
+    pub use_replace_refs: bool,
+    /// Reuse the result stored in `.git/tagver-cache` while HEAD, tags and config are
+    /// unchanged; see [`cache`](crate::cache).
+    pub cache: bool,
+    /// Remote name or URL whose missing tags are fetched first, when their commits exist
+    /// locally; see [`remote`](crate::remote). Requires the `remote-tags` feature.
+    #[cfg(feature = "remote-tags")]
+    pub remote_tags: Option<String>,
+    pub backend: Backend,
     pub verbosity: Verbosity,
 }
 
@@ -115,12 +528,55 @@ impl Default for Config {
         Self {
             work_dir: ".".into(),
             tag_prefix: "".into(),
+            tag_patterns: Vec::new(),
+            exclude_tag_patterns: Vec::new(),
+            tag_requirement: TagRequirement::default(),
+            tag_selection: TagSelection::default(),
+            allowed_signers: None,
             auto_increment: VersionPart::Patch,
             minimum_major_minor: None,
             default_prerelease_identifiers: vec!["alpha".into(), "0".into()],
+            prerelease_phases: vec!["alpha".into(), "beta".into(), "rc".into()],
             build_metadata: None,
             ignore_height: false,
+            height_metric: HeightMetric::default(),
+            height_exclude_messages: Vec::new(),
+            height_exclude_authors: Vec::new(),
+            height_format: HeightFormat::default(),
+            identifier_validation: IdentifierValidation::default(),
+            strict: false,
+            revision: None,
+            use_superproject: false,
+            use_replace_refs: true,
+            cache: false,
+            #[cfg(feature = "remote-tags")]
+            remote_tags: None,
+            backend: Backend::default(),
             verbosity: Verbosity::Normal,
         }
     }
 }
+
+impl Config {
+    /// Reject option combinations that can never produce a valid version.
+    ///
+    /// # Errors
+    /// [`TagVerError::InvalidHeightFormat`] if the height is zero-padded in the prerelease,
+    /// where leading zeros aren't valid SemVer, and [`IdentifierValidation::Error`] is set.
+    /// The other policies warn about or sanitize the padded height instead.
+    pub fn validate(&self) -> crate::error::Result<()> {
+        let format = &self.height_format;
+        if format.padding > 1
+            && format.placement == HeightPlacement::Prerelease
+            && self.identifier_validation == IdentifierValidation::Error
+        {
+            return Err(TagVerError::InvalidHeightFormat(
+                "a zero-padded height isn't a valid SemVer pre-release identifier; \
+                 place the height in build metadata, where leading zeros are allowed, \
+                 or warn about invalid identifiers instead"
+                    .to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
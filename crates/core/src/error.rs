@@ -11,6 +11,9 @@ pub enum TagVerError {
     #[error("Git repository not found at path: {0}")]
     GitRepoNotFound(String),
 
+    #[error("Repository at '{0}' is not owned by the current user. To trust it, run: git config --global --add safe.directory '{0}'")]
+    UnsafeRepository(String),
+
     #[error("No commits found in repository")]
     NoCommits,
 
@@ -29,9 +32,36 @@ pub enum TagVerError {
     #[error("Invalid semantic version: {0}")]
     InvalidSemver(String),
 
+    #[error("Invalid height format: {0}")]
+    InvalidHeightFormat(String),
+
+    #[error("Invalid version: {0}")]
+    InvalidVersion(String),
+
+    #[error("Invalid prerelease identifier: {0}")]
+    InvalidPrerelease(String),
+
+    #[error("Invalid build metadata: {0}")]
+    InvalidBuildMetadata(String),
+
+    #[error("Version overflow: {0}")]
+    VersionOverflow(String),
+
+    #[error("Invalid revision: {0}")]
+    InvalidRevision(String),
+
+    #[error("Invalid allowed signers file: {0}")]
+    InvalidAllowedSigners(String),
+
+    #[error("Failed to fetch remote tags: {0}")]
+    RemoteTags(String),
+
     #[error("No version tags found with prefix '{0}'")]
     NoVersionTags(String),
 
+    #[error("Version requirement not met: {0}")]
+    RequirementNotMet(String),
+
     #[error("Shallow repository detected - version calculation may be incorrect")]
     ShallowRepo,
 
@@ -50,3 +80,76 @@ pub enum TagVerError {
     #[error("Other error: {0}")]
     Other(String),
 }
+
+/// The class of a [`TagVerError`], for exit codes and machine-readable error reports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// No repository was found, or git refuses to use it.
+    NotARepository,
+    NoCommits,
+    ShallowRepository,
+    /// An option or the revision is invalid, or the allowed signers file can't be read.
+    InvalidConfig,
+    /// Tags yield no usable version, or the version overflows.
+    InvalidTags,
+    Io,
+    /// The version doesn't satisfy a required version range or stability.
+    RequirementNotMet,
+    /// Git and network failures, and anything else.
+    Other,
+}
+
+impl ErrorKind {
+    /// A stable identifier, such as `not_a_repository`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorKind::NotARepository => "not_a_repository",
+            ErrorKind::NoCommits => "no_commits",
+            ErrorKind::ShallowRepository => "shallow_repository",
+            ErrorKind::InvalidConfig => "invalid_config",
+            ErrorKind::InvalidTags => "invalid_tags",
+            ErrorKind::Io => "io",
+            ErrorKind::RequirementNotMet => "requirement_not_met",
+            ErrorKind::Other => "other",
+        }
+    }
+}
+
+impl std::fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl TagVerError {
+    /// The class of this error.
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            TagVerError::GitRepoNotFound(_) | TagVerError::UnsafeRepository(_) => {
+                ErrorKind::NotARepository
+            }
+            TagVerError::NoCommits => ErrorKind::NoCommits,
+            TagVerError::ShallowRepo => ErrorKind::ShallowRepository,
+            TagVerError::InvalidTagPrefix(_)
+            | TagVerError::InvalidVersionPart(_)
+            | TagVerError::InvalidMajorMinor(_)
+            | TagVerError::InvalidVerbosity(_)
+            | TagVerError::InvalidVersion(_)
+            | TagVerError::InvalidHeightFormat(_)
+            | TagVerError::InvalidPrerelease(_)
+            | TagVerError::InvalidBuildMetadata(_)
+            | TagVerError::InvalidRevision(_)
+            | TagVerError::InvalidAllowedSigners(_)
+            | TagVerError::Parse(_) => ErrorKind::InvalidConfig,
+            TagVerError::InvalidSemver(_)
+            | TagVerError::VersionOverflow(_)
+            | TagVerError::NoVersionTags(_)
+            | TagVerError::Semver(_) => ErrorKind::InvalidTags,
+            TagVerError::Io(_) => ErrorKind::Io,
+            TagVerError::RequirementNotMet(_) => ErrorKind::RequirementNotMet,
+            TagVerError::RemoteTags(_) | TagVerError::GitCommand(_) | TagVerError::Other(_) => {
+                ErrorKind::Other
+            }
+        }
+    }
+}
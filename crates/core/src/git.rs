@@ -1,10 +1,20 @@
 //! Git repository discovery and traversal functionality.
 
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
+use std::time::Instant;
 
-use crate::config::Config;
+use gix::bstr::ByteSlice;
+use regex::Regex;
+
+use crate::config::{
+    Backend, Config, HeightMetric, HeightPlacement, HeightSource, IdentifierValidation, TagPattern,
+};
 use crate::error::{Result, TagVerError};
-use crate::tags::{parse_tags, TagMap, VersionTag};
+use crate::git_cli::GitCli;
+use crate::graph::{CommitGraph, CommitInfo, TagSource};
+use crate::metadata::{self, MetadataContext};
+use crate::tags::{self, parse_tags, TagMap, VersionTag};
 use crate::version::Version;
 
 /// Git repository wrapper with tagver-specific operations.
@@ -15,13 +25,56 @@ pub struct Repository {
 
 impl Repository {
     /// Discover and open a Git repository in the given directory.
+    ///
+    /// Discovery follows the git CLI: `GIT_DIR` and `GIT_WORK_TREE` take precedence over the
+    /// directory, `GIT_CEILING_DIRECTORIES` and `GIT_DISCOVERY_ACROSS_FILESYSTEM` bound the
+    /// upward search, and a repository owned by another user is only opened when listed in
+    /// `safe.directory`.
     pub fn discover(path: impl Into<PathBuf>) -> Result<Self> {
         let path = path.into();
-
-        let repo = gix::discover(&path)
-            .map_err(|e| TagVerError::GitRepoNotFound(format!("{}: {}", path.display(), e)))?;
+        let span = tracing::info_span!(
+            "discover",
+            path = %path.display(),
+            git_dir = tracing::field::Empty,
+            shallow = tracing::field::Empty,
+            elapsed_ms = tracing::field::Empty,
+        );
+        let _entered = span.enter();
+        let start = Instant::now();
+
+        let mut trust_map = gix::sec::trust::Mapping::<gix::open::Options>::default();
+        trust_map.reduced = trust_map.reduced.bail_if_untrusted(true);
+
+        let mut ceiling_dirs = Vec::new();
+        let repo = if std::env::var_os("GIT_DIR").is_some() {
+            gix::ThreadSafeRepository::open_with_environment_overrides(&path, trust_map)
+                .map_err(gix::discover::Error::Open)
+        } else {
+            let options = discover_options();
+            ceiling_dirs.clone_from(&options.ceiling_dirs);
+            gix::ThreadSafeRepository::discover_opts(&path, options, trust_map)
+        }
+        .map_err(|e| match e {
+            gix::discover::Error::Open(gix::open::Error::UnsafeGitDir { path }) => {
+                TagVerError::UnsafeRepository(path.display().to_string())
+            }
+            e => TagVerError::GitRepoNotFound(format!("{}: {}", path.display(), e)),
+        })?
+        .to_thread_local();
+
+        if let Some(ceiling) = crossed_ceiling(&path, &repo, &ceiling_dirs) {
+            return Err(TagVerError::GitRepoNotFound(format!(
+                "{}: no repository below ceiling directory {}",
+                path.display(),
+                ceiling.display()
+            )));
+        }
 
         let is_shallow = repo.is_shallow();
+        span.record("git_dir", tracing::field::display(repo.git_dir().display()));
+        span.record("shallow", is_shallow);
+        record_elapsed(&span, start);
+        tracing::debug!("Found repository at '{}'", repo.git_dir().display());
 
         Ok(Self {
             inner: repo,
@@ -29,6 +82,32 @@ impl Repository {
         })
     }
 
+    /// Replace a submodule with the superproject containing it, repeating for nested
+    /// submodules. Any other repository is returned unchanged.
+    pub fn superproject(self) -> Result<Self> {
+        let mut repo = self;
+
+        while repo.inner.kind() == gix::repository::Kind::Submodule {
+            let Some(parent) = repo.inner.workdir().and_then(Path::parent) else {
+                break;
+            };
+            tracing::debug!("Using superproject of submodule {}", parent.display());
+            let superproject = Self::discover(parent)?;
+            // GIT_DIR pins discovery to the same repository
+            if superproject.inner.git_dir() == repo.inner.git_dir() {
+                break;
+            }
+            repo = superproject;
+        }
+
+        Ok(repo)
+    }
+
+    /// Check if the repository is bare, i.e. has no work tree.
+    pub fn is_bare(&self) -> bool {
+        self.inner.kind().is_bare()
+    }
+
     /// Check if the repository is shallow.
     pub fn is_shallow(&self) -> bool {
         self.is_shallow
@@ -45,6 +124,41 @@ impl Repository {
     }
 }
 
+/// Upward discovery options from `GIT_CEILING_DIRECTORIES` and `GIT_DISCOVERY_ACROSS_FILESYSTEM`.
+fn discover_options() -> gix::discover::upwards::Options<'static> {
+    let mut options = gix::discover::upwards::Options {
+        // Like git, ignore ceiling directories that aren't parents of `path`
+        match_ceiling_dir_or_error: false,
+        ..Default::default()
+    }
+    .apply_environment();
+
+    if let Some(value) = std::env::var_os("GIT_DISCOVERY_ACROSS_FILESYSTEM") {
+        if let Ok(value) = gix::config::Boolean::try_from(value.as_encoded_bytes().as_bstr()) {
+            options.cross_fs = value.into();
+        }
+    }
+
+    options
+}
+
+/// Return the ceiling directory that `repo` was found at or above, when searching from `path`.
+///
+/// gix searches the ceiling directory itself, while git stops right below it.
+fn crossed_ceiling(
+    path: &Path,
+    repo: &gix::Repository,
+    ceiling_dirs: &[PathBuf],
+) -> Option<PathBuf> {
+    let dir = std::fs::canonicalize(path).ok()?;
+    let found = std::fs::canonicalize(repo.workdir().unwrap_or(repo.git_dir())).ok()?;
+
+    ceiling_dirs
+        .iter()
+        .map(|ceiling| std::fs::canonicalize(ceiling).unwrap_or_else(|_| ceiling.clone()))
+        .find(|ceiling| dir.starts_with(ceiling) && ceiling.starts_with(&found))
+}
+
 /// Calculate version by traversing the commit graph.
 ///
 /// Algorithm:
@@ -58,42 +172,166 @@ impl Repository {
 /// 5. Merge build metadata
 pub fn calculate_version(repo: &Repository, config: &Config) -> Result<(Version, u32, bool)> {
     // Step 1: Parse all version tags
-    let (tag_map, _invalid_tags) = parse_tags(repo.inner(), config)?;
+    let tag_map = version_tags(repo.inner(), config)?;
+
+    // Step 2: Get HEAD commit, or the configured revision
+    let graph = GixGraph::new(repo.inner(), config);
+    let head_commit = graph.resolve(config.revision.as_deref())?;
+    let build_metadata = expand_build_metadata(config, Some(repo.inner()), head_commit)?;
+
+    calculate(
+        &graph,
+        &tag_map,
+        head_commit,
+        build_metadata.as_deref(),
+        config,
+    )
+}
 
-    // Step 2: Get HEAD commit
-    let mut head = repo
-        .inner()
-        .head()
-        .map_err(|e| TagVerError::Other(format!("Failed to get HEAD: {}", e)))?;
+/// Calculate the version with any commit graph and tag source; see [`graph`](crate::graph).
+///
+/// Build metadata templates only get the values the graph provides: `{sha}`, `{sha7}` and
+/// `{commit_date}`, besides `{ci_build}`.
+///
+/// # Errors
+/// - [`TagVerError::InvalidRevision`] if `config.revision` doesn't name a commit.
+/// - Errors of the tag source, and version synthesis errors as for [`calculate_version`].
+pub fn calculate_version_with(
+    graph: &impl CommitGraph,
+    tags: &impl TagSource,
+    config: &Config,
+) -> Result<(Version, u32, bool)> {
+    let tag_map = version_tags(tags, config)?;
+    let head_commit = graph.resolve(config.revision.as_deref())?;
+    let build_metadata = match config.build_metadata.as_deref() {
+        Some(template) => {
+            let context = MetadataContext::collect_from_graph(template, graph, head_commit);
+            metadata::expand(template, &context)?
+        }
+        None => None,
+    };
+
+    calculate(
+        graph,
+        &tag_map,
+        head_commit,
+        build_metadata.as_deref(),
+        config,
+    )
+}
 
-    let head_commit = match head.try_peel_to_id() {
-        Ok(Some(id)) => id.detach(),
-        Ok(None) | Err(_) => {
+/// Read the version tags in a `parse_tags` span, recording the number of version tags and
+/// the time taken. Tag sources record the number of invalid tags themselves.
+pub(crate) fn version_tags(tags: &(impl TagSource + ?Sized), config: &Config) -> Result<TagMap> {
+    let span = tracing::info_span!(
+        "parse_tags",
+        tags = tracing::field::Empty,
+        invalid = tracing::field::Empty,
+        elapsed_ms = tracing::field::Empty,
+    );
+    let _entered = span.enter();
+    let start = Instant::now();
+
+    let tag_map = tags.version_tags(config)?;
+    let count: usize = tag_map.values().map(Vec::len).sum();
+    span.record("tags", count);
+    record_elapsed(&span, start);
+    tracing::debug!(
+        tags = count,
+        commits = tag_map.len(),
+        "Found {} version tags on {} commits",
+        count,
+        tag_map.len()
+    );
+    Ok(tag_map)
+}
+
+/// Record the milliseconds since `start` in the `elapsed_ms` field of `span`.
+fn record_elapsed(span: &tracing::Span, start: Instant) {
+    span.record(
+        "elapsed_ms",
+        u64::try_from(start.elapsed().as_millis()).unwrap_or(u64::MAX),
+    );
+}
+
+/// Steps 3 to 5 of [`calculate_version`], for the version tags and target commit found.
+fn calculate(
+    graph: &dyn CommitGraph,
+    tag_map: &TagMap,
+    head_commit: Option<gix::ObjectId>,
+    build_metadata: Option<&str>,
+    config: &Config,
+) -> Result<(Version, u32, bool)> {
+    let head_commit = match head_commit {
+        Some(id) => id,
+        None if config.strict => return Err(TagVerError::NoCommits),
+        None => {
             // No commits - return default version
             let version = Version::default(&config.default_prerelease_identifiers);
-            let version = apply_config(version, config, None, 0);
+            let version = apply_config(version, config, build_metadata, None, 0, 0)?;
             return Ok((version, 0, false));
         }
     };
 
     // Step 3: Walk the commit graph
-    let (base_tag, height) = walk_to_tag(repo.inner(), head_commit, &tag_map)?;
+    let (base_tag, height) = {
+        let span = tracing::info_span!(
+            "walk",
+            head = %head_commit,
+            metric = ?config.height_metric,
+            base = tracing::field::Empty,
+            height = tracing::field::Empty,
+            elapsed_ms = tracing::field::Empty,
+        );
+        let _entered = span.enter();
+        let start = Instant::now();
+
+        let (base_tag, height) = find_base_tag(graph, head_commit, tag_map, config);
+        if let Some(tag) = &base_tag {
+            span.record("base", tag.tag_name.as_str());
+        }
+        span.record("height", height);
+        record_elapsed(&span, start);
+
+        match &base_tag {
+            Some(tag) => tracing::debug!(
+                tag = %tag.tag_name,
+                height,
+                "Using tag '{}' as the base, at height {}",
+                tag.tag_name,
+                height
+            ),
+            None => tracing::debug!(
+                height,
+                "No version tag found, using the default version at height {}",
+                height
+            ),
+        }
+        (base_tag, height)
+    };
 
     // Respect ignore_height by zeroing the height used for version synthesis
     let effective_height = if config.ignore_height { 0 } else { height };
+    let height_value = if effective_height > 0 {
+        height_value(graph, head_commit, effective_height, config)?
+    } else {
+        0
+    };
 
     // Step 4: Synthesize version based on tag type and height
     let (version, is_from_tag) = match base_tag {
         Some(ref tag) => {
-            let synthesized = synthesize_version(&tag.version, effective_height, config);
+            let synthesized =
+                synthesize_version(&tag.version, effective_height, height_value, config)?;
             (synthesized, height == 0)
         }
         None => {
             // No tag found - use default version
             let default = Version::default(&config.default_prerelease_identifiers);
             let version = if effective_height > 0 {
-                let mut v = default;
-                v.prerelease.push(effective_height.to_string());
+                let (identifiers, height_metadata) = height_identifiers(height_value, config);
+                let mut v = default.with_appended_prerelease(&identifiers);
+                v.build_metadata = height_metadata;
                 v
             } else {
                 default
@@ -103,106 +341,549 @@ pub fn calculate_version(repo: &Repository, config: &Config) -> Result<(Version,
     };
 
     // Step 5: Apply config (minimum, build metadata)
-    let final_version = apply_config(version, config, base_tag.as_ref(), height);
+    let final_version = apply_config(
+        version,
+        config,
+        build_metadata,
+        base_tag.as_ref(),
+        height,
+        height_value,
+    )?;
 
     Ok((final_version, height, is_from_tag))
 }
 
+/// Resolve the commit to calculate the version for: `config.revision` when set, else HEAD.
+///
+/// Returns `None` when HEAD is unborn. A revision that doesn't name a commit is an error.
+pub(crate) fn target_commit(
+    repo: &gix::Repository,
+    config: &Config,
+) -> Result<Option<gix::ObjectId>> {
+    resolve_commit(repo, config.revision.as_deref())
+}
+
+fn resolve_commit(repo: &gix::Repository, revision: Option<&str>) -> Result<Option<gix::ObjectId>> {
+    if let Some(revision) = revision {
+        let commit = repo
+            .rev_parse_single(revision)
+            .map_err(|e| TagVerError::InvalidRevision(format!("{}: {}", revision, e)))?
+            .object()
+            .map_err(|e| TagVerError::InvalidRevision(format!("{}: {}", revision, e)))?
+            .peel_to_commit()
+            .map_err(|e| TagVerError::InvalidRevision(format!("{}: {}", revision, e)))?;
+        return Ok(Some(commit.id));
+    }
+
+    let mut head = repo
+        .head()
+        .map_err(|e| TagVerError::Other(format!("Failed to get HEAD: {}", e)))?;
+
+    Ok(head.try_peel_to_id().ok().flatten().map(|id| id.detach()))
+}
+
+/// Find the base tag and the height according to `config.height_metric`.
+pub(crate) fn find_base_tag(
+    parents: &dyn CommitGraph,
+    head: gix::ObjectId,
+    tag_map: &TagMap,
+    config: &Config,
+) -> (Option<VersionTag>, u32) {
+    let filter = HeightFilter::new(parents, config);
+
+    match config.height_metric {
+        HeightMetric::FirstParent => {
+            let (tag, _, height) = walk_to_tag(parents, head, tag_map, &filter);
+            (tag, height)
+        }
+        HeightMetric::MinVer => minver_walk(parents, head, tag_map, &filter),
+        HeightMetric::Ancestry | HeightMetric::NoMerges => {
+            let (tag, base, _) = walk_to_tag(parents, head, tag_map, &HeightFilter::none(parents));
+            if base == head {
+                return (tag, 0);
+            }
+
+            let exclude_merges = config.height_metric == HeightMetric::NoMerges;
+            let height = ancestry_height(parents, head, base, exclude_merges, &filter);
+            (tag, height)
+        }
+    }
+}
+
 /// Walk from a commit towards ancestors, looking for a tagged commit.
-/// Returns the found tag (if any) and the height (number of commits walked).
+/// Returns the found tag (if any), the commit the walk stopped at (the tagged commit or
+/// the root) and the height (number of commits walked that count).
 fn walk_to_tag(
-    repo: &gix::Repository,
+    parents: &dyn CommitGraph,
     start: gix::ObjectId,
     tag_map: &TagMap,
-) -> Result<(Option<VersionTag>, u32)> {
+    filter: &HeightFilter<'_>,
+) -> (Option<VersionTag>, gix::ObjectId, u32) {
     let mut height: u32 = 0;
     let mut current = start;
 
     loop {
         // Check if current commit has a tag
-        if let Some(tags) = tag_map.get(&current) {
-            // Tags are sorted highest first, use the first one
-            if let Some(tag) = tags.first() {
-                return Ok((Some(tag.clone()), height));
+        if let Some(tag) = tag_map
+            .get(&current)
+            .and_then(|tags| tags::select_tag(tags))
+        {
+            return (Some(tag.clone()), current, height);
+        }
+
+        // Move to first parent and increment height; no parent means we've reached the root
+        match parents.first_parent(current) {
+            Some(parent) => {
+                if filter.counts(current) {
+                    height += 1;
+                }
+                current = parent;
+            }
+            None => break,
+        }
+    }
+
+    (None, current, height)
+}
+
+/// Walk all parents depth-first like MinVer, first parents first, without passing tagged
+/// commits. The highest version found wins, ties going to the tag found first; its height
+/// is the length of the path it was found on. Without tags, the height is that of the first
+/// root commit found.
+fn minver_walk(
+    parents: &dyn CommitGraph,
+    start: gix::ObjectId,
+    tag_map: &TagMap,
+    filter: &HeightFilter<'_>,
+) -> (Option<VersionTag>, u32) {
+    let mut checked = HashSet::new();
+    let mut pending = vec![(start, 0u32)];
+    let mut best: Option<(VersionTag, u32)> = None;
+    let mut root_height = None;
+
+    while let Some((id, height)) = pending.pop() {
+        if !checked.insert(id) {
+            continue;
+        }
+
+        if let Some(tag) = tag_map.get(&id).and_then(|tags| tags::select_tag(tags)) {
+            let is_higher = match &best {
+                Some((best, _)) => tag.version > best.version,
+                None => true,
+            };
+            if is_higher {
+                best = Some((tag.clone(), height));
             }
+            continue;
+        }
+
+        let commit_parents = parents.parents(id).unwrap_or_default();
+        if commit_parents.is_empty() {
+            root_height.get_or_insert(height);
+        }
+        let parent_height = height + u32::from(filter.counts(id));
+        // Reversed, so the first parent is popped first
+        for parent in commit_parents.into_iter().rev() {
+            pending.push((parent, parent_height));
+        }
+    }
+
+    match best {
+        Some((tag, height)) => (Some(tag), height),
+        None => (None, root_height.unwrap_or(0)),
+    }
+}
+
+/// Count the commits reachable from `head` but not from `base`, optionally without merges.
+fn ancestry_height(
+    parents: &dyn CommitGraph,
+    head: gix::ObjectId,
+    base: gix::ObjectId,
+    exclude_merges: bool,
+    filter: &HeightFilter<'_>,
+) -> u32 {
+    let mut height = 0;
+    for (id, commit_parents) in parents.reachable(&[head], Some(base)) {
+        if !(exclude_merges && commit_parents.len() > 1) && filter.counts(id) {
+            height += 1;
+        }
+    }
+    height
+}
+
+/// Excludes commits from the height by message or author email, per
+/// `config.height_exclude_messages` and `config.height_exclude_authors`.
+struct HeightFilter<'a> {
+    commits: &'a dyn CommitGraph,
+    messages: &'a [Regex],
+    authors: &'a [TagPattern],
+}
+
+impl<'a> HeightFilter<'a> {
+    fn new(commits: &'a dyn CommitGraph, config: &'a Config) -> Self {
+        Self {
+            commits,
+            messages: &config.height_exclude_messages,
+            authors: &config.height_exclude_authors,
         }
+    }
 
-        // Get the commit object
-        let commit = match repo.find_object(current) {
-            Ok(obj) => match obj.try_into_commit() {
-                Ok(c) => c,
-                Err(_) => break,
-            },
-            Err(_) => break,
+    /// A filter counting every commit.
+    fn none(commits: &'a dyn CommitGraph) -> Self {
+        Self {
+            commits,
+            messages: &[],
+            authors: &[],
+        }
+    }
+
+    /// Whether `id` counts toward the height. Unreadable commits count.
+    fn counts(&self, id: gix::ObjectId) -> bool {
+        if self.messages.is_empty() && self.authors.is_empty() {
+            return true;
+        }
+        let Some(commit) = self.commits.commit_info(id) else {
+            return true;
         };
 
-        // Get first parent (for first-parent traversal)
-        let parents: Vec<_> = commit.parent_ids().collect();
+        if let Some(regex) = self
+            .messages
+            .iter()
+            .find(|regex| regex.is_match(&commit.message))
+        {
+            tracing::trace!("Commit {} excluded from height by '{}'", id, regex);
+            return false;
+        }
 
-        // If no parents, we've reached the root
-        if parents.is_empty() {
-            break;
+        if let Some(pattern) = self
+            .authors
+            .iter()
+            .find(|pattern| pattern.matches(&commit.author_email))
+        {
+            tracing::trace!("Commit {} excluded from height by '{}'", id, pattern);
+            return false;
         }
 
-        // Move to first parent and increment height
-        current = parents[0].detach();
-        height += 1;
+        true
     }
+}
+
+/// Default namespace of replace refs, overridden by `GIT_REPLACE_REF_BASE`.
+const REPLACE_REF_BASE: &str = "refs/replace/";
 
-    Ok((None, height))
+/// Maximum length of a chain of replacements, as in git.
+const MAX_REPLACE_DEPTH: usize = 5;
+
+/// The gix [`CommitGraph`], backed by the commit-graph file when present, honoring replace refs.
+///
+/// The commit-graph (`.git/objects/info/commit-graph`) stores parent positions for every
+/// commit it covers, so stepping through history doesn't need to find and decode commit
+/// objects. Commits created after the graph was written are looked up in the object database.
+///
+/// A replace ref `refs/replace/<id>`, e.g. from `git replace --graft`, substitutes another
+/// commit for `<id>` while reading it, so traversal follows the same history as `git log`.
+/// Like git, the commit-graph isn't used while replacements exist, since it records the
+/// original parents.
+pub struct GixGraph<'repo> {
+    repo: &'repo gix::Repository,
+    graph: Option<gix::commitgraph::Graph>,
+    replacements: HashMap<gix::ObjectId, gix::ObjectId>,
+}
+
+impl<'repo> GixGraph<'repo> {
+    /// Create the graph of `repo`, with the replace refs in effect for `config`.
+    pub fn new(repo: &'repo gix::Repository, config: &Config) -> Self {
+        let replacements = replace_refs(repo, config);
+        // An unreadable commit-graph is only a missed optimization
+        let graph = if replacements.is_empty() {
+            repo.commit_graph_if_enabled().ok().flatten()
+        } else {
+            None
+        };
+        Self {
+            repo,
+            graph,
+            replacements,
+        }
+    }
+
+    /// Read commit `id`, or its replacement.
+    fn commit(&self, id: gix::ObjectId) -> Option<gix::Commit<'repo>> {
+        let mut source = id;
+        for _ in 0..MAX_REPLACE_DEPTH {
+            match self.replacements.get(&source) {
+                Some(replacement) => source = *replacement,
+                None => break,
+            }
+        }
+        self.repo.find_commit(source).ok()
+    }
+}
+
+impl CommitGraph for GixGraph<'_> {
+    fn resolve(&self, revision: Option<&str>) -> Result<Option<gix::ObjectId>> {
+        resolve_commit(self.repo, revision)
+    }
+
+    fn first_parent(&self, id: gix::ObjectId) -> Option<gix::ObjectId> {
+        if let Some(graph) = &self.graph {
+            if let Some(commit) = graph.commit_by_id(id) {
+                if let Ok(parent) = commit.parent1() {
+                    return parent.map(|position| graph.id_at(position).to_owned());
+                }
+            }
+        }
+
+        self.commit(id)?.parent_ids().next().map(|id| id.detach())
+    }
+
+    fn parents(&self, id: gix::ObjectId) -> Option<Vec<gix::ObjectId>> {
+        if let Some(graph) = &self.graph {
+            if let Some(commit) = graph.commit_by_id(id) {
+                let parents: std::result::Result<Vec<_>, _> = commit
+                    .iter_parents()
+                    .map(|position| position.map(|position| graph.id_at(position).to_owned()))
+                    .collect();
+                if let Ok(parents) = parents {
+                    return Some(parents);
+                }
+            }
+        }
+
+        let commit = self.commit(id)?;
+        let parents = commit.parent_ids().map(|id| id.detach()).collect();
+        Some(parents)
+    }
+
+    fn commit_info(&self, id: gix::ObjectId) -> Option<CommitInfo> {
+        let commit = self.commit(id)?;
+        let info = CommitInfo {
+            message: commit.message_raw_sloppy().to_str_lossy().into_owned(),
+            author_email: commit
+                .author()
+                .map(|author| author.email.to_str_lossy().into_owned())
+                .unwrap_or_default(),
+            time: commit.time().map(|time| time.seconds).unwrap_or_default(),
+        };
+        Some(info)
+    }
+}
+
+impl TagSource for gix::Repository {
+    fn version_tags(&self, config: &Config) -> Result<TagMap> {
+        parse_tags(self, config).map(|(tag_map, _)| tag_map)
+    }
+}
+
+/// The replace refs in effect, mapping replaced objects to their replacements.
+///
+/// Empty when `config.use_replace_refs` is off, `GIT_NO_REPLACE_OBJECTS` is set or
+/// `core.useReplaceRefs` is false, as in git. `GIT_REPLACE_REF_BASE` changes the namespace.
+pub(crate) fn replace_refs(
+    repo: &gix::Repository,
+    config: &Config,
+) -> HashMap<gix::ObjectId, gix::ObjectId> {
+    let enabled = config.use_replace_refs
+        && std::env::var_os("GIT_NO_REPLACE_OBJECTS").is_none()
+        && repo
+            .config_snapshot()
+            .boolean("core.useReplaceRefs")
+            .unwrap_or(true);
+    if !enabled {
+        return HashMap::new();
+    }
+
+    let mut base = std::env::var("GIT_REPLACE_REF_BASE")
+        .ok()
+        .filter(|base| !base.is_empty())
+        .unwrap_or_else(|| REPLACE_REF_BASE.to_string());
+    if !base.ends_with('/') {
+        base.push('/');
+    }
+
+    let Ok(platform) = repo.references() else {
+        return HashMap::new();
+    };
+    let Ok(refs) = platform.prefixed(base.as_str()) else {
+        return HashMap::new();
+    };
+
+    refs.flatten()
+        .filter_map(|reference| {
+            let target = reference.target().try_id()?.to_owned();
+            let name = reference.name().as_bstr();
+            let source = gix::ObjectId::from_hex(name.strip_prefix(base.as_bytes())?).ok()?;
+            Some((source, target))
+        })
+        .collect()
 }
 
 /// Synthesize version based on base tag, height, and config.
-fn synthesize_version(base: &Version, height: u32, config: &Config) -> Version {
+fn synthesize_version(
+    base: &Version,
+    height: u32,
+    height_value: u64,
+    config: &Config,
+) -> Result<Version> {
     if height == 0 {
         // Exactly on tag - use as-is (build metadata handled later)
-        return base.clone();
+        return Ok(base.clone());
     }
 
-    if base.is_prerelease() {
+    let (identifiers, height_metadata) = height_identifiers(height_value, config);
+
+    let mut version = if base.is_prerelease() {
         // Pre-release: append height
         // 1.0.0-beta.1 + height=3 -> 1.0.0-beta.1.3
-        base.with_prerelease_height(height)
+        base.with_appended_prerelease(&identifiers)
     } else {
         // RTM: increment + default prerelease + height
         // 1.0.0 + Patch + height=5 -> 1.0.1-alpha.0.5
-        base.with_rtm_height(
-            height,
+        base.with_rtm_prerelease(
+            &identifiers,
             &config.auto_increment,
             &config.default_prerelease_identifiers,
-        )
+        )?
+    };
+    version.build_metadata = height_metadata;
+
+    Ok(version)
+}
+
+/// Split the formatted height into prerelease identifiers and build metadata,
+/// according to the configured placement.
+fn height_identifiers(height_value: u64, config: &Config) -> (Vec<String>, Option<String>) {
+    let identifiers = config.height_format.identifiers(height_value);
+
+    match config.height_format.placement {
+        HeightPlacement::Prerelease => (identifiers, None),
+        HeightPlacement::BuildMetadata => (Vec::new(), Some(identifiers.join("."))),
+    }
+}
+
+/// Resolve the value written in place of the height.
+fn height_value(
+    graph: &dyn CommitGraph,
+    head: gix::ObjectId,
+    height: u32,
+    config: &Config,
+) -> Result<u64> {
+    match config.height_format.source {
+        HeightSource::Height => Ok(u64::from(height)),
+        HeightSource::Timestamp => {
+            let time = graph
+                .commit_info(head)
+                .ok_or_else(|| TagVerError::Other(format!("Failed to find HEAD commit {}", head)))?
+                .time;
+
+            // Render in UTC so the value doesn't depend on the committer's timezone
+            metadata::format_time(time, "%Y%m%d%H%M%S")
+                .parse()
+                .map_err(|e| TagVerError::Other(format!("Invalid HEAD commit time: {}", e)))
+        }
+        HeightSource::CommitCount => Ok(graph.reachable(&[head], None).len() as u64),
     }
 }
 
-/// Apply configuration constraints and metadata.
+/// Apply configuration constraints and metadata, then validate the identifiers.
+///
+/// `config_metadata` is the configured build metadata after template expansion, and
+/// `height_value` the height used in the version (0 when the height is ignored).
 fn apply_config(
     mut version: Version,
     config: &Config,
+    config_metadata: Option<&str>,
     tag: Option<&VersionTag>,
     height: u32,
-) -> Version {
+    height_value: u64,
+) -> Result<Version> {
     // Apply minimum major.minor
     // Only apply if we are not exactly on a tag, or if there is no tag
     if let Some(ref min) = config.minimum_major_minor {
         if height > 0 || tag.is_none() {
-            version = version.apply_minimum(min, &config.default_prerelease_identifiers);
+            let minimum = version.apply_minimum(min, &config.default_prerelease_identifiers);
+            // Like MinVer, a bumped version keeps the height
+            if minimum != version && height_value > 0 {
+                let (identifiers, height_metadata) = height_identifiers(height_value, config);
+                version = minimum.with_appended_prerelease(&identifiers);
+                version.build_metadata = height_metadata;
+            } else {
+                version = minimum;
+            }
         }
     }
 
     // Merge build metadata
     let tag_metadata = tag.and_then(|t| t.version.build_metadata.as_deref());
-    let config_metadata = config.build_metadata.as_deref();
+    let height_metadata = version.build_metadata.clone();
 
-    // Only merge build metadata if we're on a tag or config provides it
-    if tag_metadata.is_some() || config_metadata.is_some() {
+    // Only merge build metadata if we're on a tag, config provides it, or it holds the height
+    if tag_metadata.is_some() || config_metadata.is_some() || height_metadata.is_some() {
         // For height > 0, tag metadata is NOT carried forward
-        // Only config metadata is used
-        let effective_tag_metadata = if height == 0 { tag_metadata } else { None };
+        // Only config metadata and a height placed in build metadata are used
+        let effective_tag_metadata = if height == 0 {
+            tag_metadata
+        } else {
+            height_metadata.as_deref()
+        };
 
         version = version.with_merged_build_metadata(effective_tag_metadata, config_metadata);
     }
 
-    version
+    validate_identifiers(version, config)
+}
+
+/// Enforce the configured identifier validation policy.
+fn validate_identifiers(version: Version, config: &Config) -> Result<Version> {
+    let Err(e) = version.validate() else {
+        return Ok(version);
+    };
+
+    match config.identifier_validation {
+        IdentifierValidation::Error => Err(e),
+        IdentifierValidation::Warn => {
+            tracing::warn!("{}. The version is not valid SemVer.", e);
+            Ok(version)
+        }
+        IdentifierValidation::Sanitize => {
+            let sanitized = version.sanitized();
+            tracing::warn!("{}. Using '{}' instead.", e, sanitized);
+            Ok(sanitized)
+        }
+    }
+}
+
+/// Expand the configured build metadata template, if any.
+fn expand_build_metadata(
+    config: &Config,
+    repo: Option<&gix::Repository>,
+    commit: Option<gix::ObjectId>,
+) -> Result<Option<String>> {
+    match config.build_metadata.as_deref() {
+        Some(template) => {
+            let context =
+                MetadataContext::collect(template, repo, commit, config.revision.as_deref())?;
+            metadata::expand(template, &context)
+        }
+        None => Ok(None),
+    }
+}
+
+/// Apply `config.use_superproject` and fetch `config.remote_tags` (with the `remote-tags`
+/// feature) for a discovered repository.
+pub(crate) fn prepare_repository(repo: Repository, config: &Config) -> Result<Repository> {
+    let repo = if config.use_superproject {
+        repo.superproject()?
+    } else {
+        repo
+    };
+
+    #[cfg(feature = "remote-tags")]
+    if let Some(remote) = &config.remote_tags {
+        crate::remote::fetch_tags(repo.inner(), remote, config)?;
+    }
+
+    Ok(repo)
 }
 
 /// Calculate version, handling the case where no repository is found.
@@ -213,11 +894,21 @@ pub fn calculate_version_fallback(
 ) -> Result<(Version, u32, bool)> {
     let work_dir = work_dir.into();
 
-    match Repository::discover(&work_dir) {
-        Ok(repo) => calculate_version(&repo, config),
+    let result = match config.backend {
+        Backend::Gix => Repository::discover(&work_dir)
+            .map(|repo| calculate_version(&prepare_repository(repo, config)?, config)),
+        Backend::Git => GitCli::discover(&work_dir).map(|git| {
+            let git = git.with_config(config);
+            calculate_version_with(&git, &git, config)
+        }),
+    };
+
+    match result {
+        Ok(result) => result,
         Err(TagVerError::GitRepoNotFound(_)) => {
+            let build_metadata = expand_build_metadata(config, None, None)?;
             let version = Version::default(&config.default_prerelease_identifiers);
-            let version = apply_config(version, config, None, 0);
+            let version = apply_config(version, config, build_metadata.as_deref(), None, 0, 0)?;
             Ok((version, 0, false))
         }
         Err(e) => Err(e),
@@ -226,7 +917,5 @@ pub fn calculate_version_fallback(
 
 /// Check if the given directory is a valid Git working directory.
 pub fn is_git_directory(path: impl Into<PathBuf>) -> bool {
-    let path = path.into();
-
-    gix::discover(path).is_ok()
+    Repository::discover(path).is_ok()
 }
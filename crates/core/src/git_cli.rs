@@ -0,0 +1,265 @@
+//! A [`CommitGraph`] and [`TagSource`] backed by the `git` executable.
+//!
+//! An escape hatch for repositories gix can't read, selected with
+//! [`Backend::Git`](crate::config::Backend). git itself applies replace refs, grafts and
+//! shallow boundaries, so the graph is the one `git log` shows.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::config::Config;
+use crate::error::{Result, TagVerError};
+use crate::graph::{CommitGraph, CommitInfo, TagSource};
+use crate::tags::{self, SkipReason, TagFilter, TagMap, VersionTag};
+
+/// The repository containing a directory, read by running `git`.
+pub struct GitCli {
+    work_dir: PathBuf,
+    /// Run git with `--no-replace-objects`.
+    no_replace_objects: bool,
+    /// Commits read so far, with their parents and details.
+    commits: RefCell<HashMap<gix::ObjectId, (Vec<gix::ObjectId>, CommitInfo)>>,
+}
+
+impl GitCli {
+    /// Use the repository containing `work_dir`, as found by `git rev-parse`.
+    ///
+    /// # Errors
+    /// - [`TagVerError::GitRepoNotFound`] if git finds no repository.
+    /// - [`TagVerError::GitCommand`] if git can't be run.
+    pub fn discover(work_dir: impl Into<PathBuf>) -> Result<Self> {
+        let git = Self {
+            work_dir: work_dir.into(),
+            no_replace_objects: false,
+            commits: RefCell::default(),
+        };
+
+        let output = git.output(&["rev-parse", "--git-dir"])?;
+        if !output.status.success() {
+            return Err(TagVerError::GitRepoNotFound(format!(
+                "{}: {}",
+                git.work_dir.display(),
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+
+        Ok(git)
+    }
+
+    /// Apply `config.use_replace_refs`; git applies the environment and `core.useReplaceRefs`.
+    pub fn with_config(mut self, config: &Config) -> Self {
+        self.no_replace_objects = !config.use_replace_refs;
+        self
+    }
+
+    /// The directory git runs in.
+    pub fn work_dir(&self) -> &Path {
+        &self.work_dir
+    }
+
+    /// Check if the repository is shallow.
+    pub fn is_shallow(&self) -> bool {
+        self.run(&["rev-parse", "--is-shallow-repository"])
+            .is_ok_and(|output| output.trim() == "true")
+    }
+
+    fn output(&self, args: &[&str]) -> Result<std::process::Output> {
+        let mut command = Command::new("git");
+        if self.no_replace_objects {
+            command.arg("--no-replace-objects");
+        }
+        command
+            .args(["-c", "log.showSignature=false"])
+            .args(args)
+            .current_dir(&self.work_dir)
+            .output()
+            .map_err(|e| TagVerError::GitCommand(format!("Failed to run git: {}", e)))
+    }
+
+    /// Run git, returning its stdout.
+    fn run(&self, args: &[&str]) -> Result<String> {
+        let stdout = self.run_raw(args)?;
+        Ok(String::from_utf8_lossy(&stdout).into_owned())
+    }
+
+    fn run_raw(&self, args: &[&str]) -> Result<Vec<u8>> {
+        let output = self.output(args)?;
+        if !output.status.success() {
+            return Err(TagVerError::GitCommand(format!(
+                "git {}: {}",
+                args.join(" "),
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        Ok(output.stdout)
+    }
+
+    /// Peel `object` through every tag it points at, returning the type and id of the
+    /// object at the end.
+    fn peel(&self, object: &str) -> Result<(String, String)> {
+        let id = self.run(&[
+            "rev-parse",
+            "--verify",
+            "--quiet",
+            &format!("{}^{{}}", object),
+        ])?;
+        let kind = self.run(&["cat-file", "-t", id.trim()])?;
+        Ok((kind.trim().to_string(), id.trim().to_string()))
+    }
+
+    /// Read `id` and its ancestors with a single `git log`, unless already read.
+    fn load(&self, id: gix::ObjectId) {
+        if self.commits.borrow().contains_key(&id) {
+            return;
+        }
+
+        let hex = id.to_string();
+        let Ok(log) = self.run(&["log", "-z", "--format=%H %P%n%ct%n%ae%n%B", &hex, "--"]) else {
+            return;
+        };
+
+        let mut commits = self.commits.borrow_mut();
+        for record in log.split('\0').filter(|record| !record.is_empty()) {
+            let mut lines = record.splitn(4, '\n');
+            let mut ids = lines
+                .next()
+                .unwrap_or_default()
+                .split_whitespace()
+                .filter_map(|hex| gix::ObjectId::from_hex(hex.as_bytes()).ok());
+            let Some(commit) = ids.next() else {
+                continue;
+            };
+            let info = CommitInfo {
+                time: lines
+                    .next()
+                    .and_then(|time| time.parse().ok())
+                    .unwrap_or_default(),
+                author_email: lines.next().unwrap_or_default().to_string(),
+                message: lines.next().unwrap_or_default().to_string(),
+            };
+            commits.insert(commit, (ids.collect(), info));
+        }
+    }
+}
+
+impl CommitGraph for GitCli {
+    fn resolve(&self, revision: Option<&str>) -> Result<Option<gix::ObjectId>> {
+        let spec = format!("{}^{{commit}}", revision.unwrap_or("HEAD"));
+        let output = self.output(&["rev-parse", "--verify", "--quiet", &spec])?;
+
+        if !output.status.success() {
+            return match revision {
+                Some(revision) => Err(TagVerError::InvalidRevision(format!(
+                    "{}: not a commit",
+                    revision
+                ))),
+                // Unborn HEAD
+                None => Ok(None),
+            };
+        }
+
+        let hex = String::from_utf8_lossy(&output.stdout);
+        gix::ObjectId::from_hex(hex.trim().as_bytes())
+            .map(Some)
+            .map_err(|e| TagVerError::GitCommand(format!("Invalid commit id '{}': {}", hex, e)))
+    }
+
+    fn parents(&self, id: gix::ObjectId) -> Option<Vec<gix::ObjectId>> {
+        self.load(id);
+        self.commits
+            .borrow()
+            .get(&id)
+            .map(|(parents, _)| parents.clone())
+    }
+
+    fn commit_info(&self, id: gix::ObjectId) -> Option<CommitInfo> {
+        self.load(id);
+        self.commits.borrow().get(&id).map(|(_, info)| info.clone())
+    }
+}
+
+impl TagSource for GitCli {
+    fn version_tags(&self, config: &Config) -> Result<TagMap> {
+        let allowed_signers = match &config.allowed_signers {
+            Some(path) => Some(path.clone()),
+            None => self
+                .run(&["config", "--path", "gpg.ssh.allowedSignersFile"])
+                .ok()
+                .map(|path| PathBuf::from(path.trim()))
+                .filter(|path| !path.as_os_str().is_empty()),
+        };
+        let filter = TagFilter::with_allowed_signers(config, allowed_signers)?;
+
+        let refs = self.run(&[
+            "for-each-ref",
+            "--format=%(refname)%00%(objecttype)%00%(objectname)%00%(*objecttype)%00%(*objectname)%00%(taggerdate:unix)",
+            "refs/tags",
+        ])?;
+
+        let mut tag_map = TagMap::new();
+        let mut invalid = 0;
+        for line in refs.lines() {
+            let fields: Vec<&str> = line.split('\0').collect();
+            let [refname, kind, object, peeled_kind, peeled, tagger_time] = fields[..] else {
+                continue;
+            };
+            let tag_name = refname.strip_prefix("refs/tags/").unwrap_or(refname);
+            let version = match tags::tag_version(tag_name, config) {
+                Ok(version) => version,
+                Err(reason) => {
+                    invalid += usize::from(reason == SkipReason::InvalidVersion);
+                    tags::log_skipped(tag_name, &reason);
+                    continue;
+                }
+            };
+
+            let annotated = kind == "tag";
+            if filter.needs_tag_object() {
+                let data = match annotated {
+                    true => Some(self.run_raw(&["cat-file", "tag", object])?),
+                    false => None,
+                };
+                if let Err(reason) = filter.check_tag_object(data.as_deref()) {
+                    tags::log_skipped(tag_name, &reason);
+                    continue;
+                }
+            }
+
+            // for-each-ref peels one level, so a tag of a tag is peeled fully here
+            let nested;
+            let (kind, target) = match annotated {
+                true if peeled_kind == "tag" => match self.peel(object) {
+                    Ok(peeled) => {
+                        nested = peeled;
+                        (nested.0.as_str(), nested.1.as_str())
+                    }
+                    Err(_) => {
+                        tags::log_skipped(tag_name, &SkipReason::Unresolved);
+                        continue;
+                    }
+                },
+                true => (peeled_kind, peeled),
+                false => (kind, object),
+            };
+            if kind != "commit" {
+                tags::log_skipped(tag_name, &SkipReason::NotACommit(kind.to_string()));
+                continue;
+            }
+            let Ok(target) = gix::ObjectId::from_hex(target.as_bytes()) else {
+                continue;
+            };
+
+            tag_map.entry(target).or_default().push(VersionTag {
+                version,
+                tag_name: tag_name.to_string(),
+                tagger_time: tagger_time.parse().ok(),
+            });
+        }
+
+        tracing::Span::current().record("invalid", invalid);
+        tags::sort_tag_map(&mut tag_map, config.tag_selection);
+        Ok(tag_map)
+    }
+}
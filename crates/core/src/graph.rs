@@ -0,0 +1,260 @@
+//! Commit graphs and tag sources that version calculation runs against.
+//!
+//! [`calculate_version_with`](crate::git::calculate_version_with) walks any [`CommitGraph`]
+//! and takes the version tags from any [`TagSource`]. There are three backends:
+//!
+//! | Backend | Graph | Tags | Use |
+//! |---------|-------|------|-----|
+//! | gix | [`GixGraph`](crate::git::GixGraph) | [`gix::Repository`] | The default, reading the repository directly |
+//! | In-memory | [`MemoryGraph`] | [`MemoryGraph`] | Tests and property-based checks |
+//! | git CLI | [`GitCli`](crate::git_cli::GitCli) | [`GitCli`](crate::git_cli::GitCli) | Repository formats gix can't read |
+
+use std::collections::{HashMap, HashSet};
+
+use crate::config::{Config, TagRequirement};
+use crate::error::{Result, TagVerError};
+use crate::tags::{self, SkipReason, TagMap, VersionTag};
+
+/// The commit details used to exclude commits from the height and as a timestamp height.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CommitInfo {
+    /// The raw commit message.
+    pub message: String,
+    pub author_email: String,
+    /// Committer time, in seconds since the Unix epoch.
+    pub time: i64,
+}
+
+/// A commit graph to walk from HEAD towards the version tags.
+pub trait CommitGraph {
+    /// Resolve `revision`, or HEAD when `None`, to a commit. Returns `None` when HEAD is
+    /// unborn.
+    ///
+    /// # Errors
+    /// [`TagVerError::InvalidRevision`] if `revision` doesn't name a commit.
+    fn resolve(&self, revision: Option<&str>) -> Result<Option<gix::ObjectId>>;
+
+    /// Return all parents of `id`, first parent first, or `None` when the commit can't be
+    /// read (e.g. the boundary of a shallow clone).
+    fn parents(&self, id: gix::ObjectId) -> Option<Vec<gix::ObjectId>>;
+
+    /// Return the first parent of `id`, or `None` for a root commit or an unreadable one.
+    fn first_parent(&self, id: gix::ObjectId) -> Option<gix::ObjectId> {
+        self.parents(id)?.first().copied()
+    }
+
+    /// Return the message, author and time of `id`, or `None` when it can't be read.
+    fn commit_info(&self, id: gix::ObjectId) -> Option<CommitInfo>;
+
+    /// The commits reachable from `tips` but not from `hidden`, like
+    /// `git rev-list <tips> ^<hidden>`, each with its parents. Unreadable commits, such as
+    /// those beyond the boundary of a shallow clone, are left out.
+    fn reachable(
+        &self,
+        tips: &[gix::ObjectId],
+        hidden: Option<gix::ObjectId>,
+    ) -> Vec<(gix::ObjectId, Vec<gix::ObjectId>)> {
+        let excluded: HashSet<gix::ObjectId> = match hidden {
+            Some(hidden) => self
+                .reachable(&[hidden], None)
+                .into_iter()
+                .map(|(id, _)| id)
+                .collect(),
+            None => HashSet::new(),
+        };
+
+        let mut seen = HashSet::new();
+        let mut pending = tips.to_vec();
+        let mut commits = Vec::new();
+
+        while let Some(id) = pending.pop() {
+            if excluded.contains(&id) || !seen.insert(id) {
+                continue;
+            }
+            let Some(parents) = self.parents(id) else {
+                continue;
+            };
+            pending.extend(parents.iter().copied());
+            commits.push((id, parents));
+        }
+
+        commits
+    }
+}
+
+/// A source of version tags.
+pub trait TagSource {
+    /// The tags selected as version tags by `config`, by the commit they point to, highest
+    /// version first.
+    ///
+    /// # Errors
+    /// [`TagVerError::InvalidAllowedSigners`] if tags are verified and the allowed signers
+    /// file can't be read, or an error of the backend.
+    fn version_tags(&self, config: &Config) -> Result<TagMap>;
+}
+
+/// A commit graph with lightweight tags, built in code.
+///
+/// Commits are named, and get an id derived from the order they are added in. HEAD is the
+/// last commit added unless set with [`MemoryGraph::set_head`].
+///
+/// # Examples
+/// ```rust
+/// use tagver::graph::MemoryGraph;
+/// use tagver::{git, Config};
+///
+/// let mut graph = MemoryGraph::new();
+/// graph.commit("root", &[]);
+/// graph.tag("1.0.0", "root");
+/// graph.commit("feature", &["root"]);
+/// graph.commit("main", &["root"]);
+/// graph.commit("merge", &["main", "feature"]);
+///
+/// let (version, height, _) = git::calculate_version_with(&graph, &graph, &Config::default())?;
+/// assert_eq!(version.to_string(), "1.0.1-alpha.0.2");
+/// assert_eq!(height, 2);
+/// # Ok::<_, tagver::TagVerError>(())
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MemoryGraph {
+    ids: HashMap<String, gix::ObjectId>,
+    commits: HashMap<gix::ObjectId, (Vec<gix::ObjectId>, CommitInfo)>,
+    tags: Vec<(String, gix::ObjectId)>,
+    head: Option<gix::ObjectId>,
+}
+
+impl MemoryGraph {
+    /// Create an empty graph, whose HEAD is unborn.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a commit named `name` with the named parents, returning its id. The message is
+    /// the name.
+    ///
+    /// # Panics
+    /// If a parent hasn't been added.
+    pub fn commit(&mut self, name: &str, parents: &[&str]) -> gix::ObjectId {
+        let info = CommitInfo {
+            message: name.to_string(),
+            ..Default::default()
+        };
+        self.commit_with(name, parents, info)
+    }
+
+    /// Add a commit named `name` with the named parents and the given details, returning
+    /// its id.
+    ///
+    /// # Panics
+    /// If a parent hasn't been added.
+    pub fn commit_with(&mut self, name: &str, parents: &[&str], info: CommitInfo) -> gix::ObjectId {
+        let parents = parents
+            .iter()
+            .map(|parent| {
+                self.id(parent)
+                    .unwrap_or_else(|| panic!("Unknown parent commit '{}'", parent))
+            })
+            .collect();
+
+        let mut bytes = [0u8; 20];
+        bytes[12..].copy_from_slice(&(self.commits.len() as u64 + 1).to_be_bytes());
+        let id = gix::ObjectId::from_bytes_or_panic(&bytes);
+
+        self.ids.insert(name.to_string(), id);
+        self.commits.insert(id, (parents, info));
+        self.head = Some(id);
+        id
+    }
+
+    /// Add a lightweight tag on the named commit.
+    ///
+    /// # Panics
+    /// If the commit hasn't been added.
+    pub fn tag(&mut self, tag_name: &str, commit: &str) {
+        let id = self
+            .id(commit)
+            .unwrap_or_else(|| panic!("Unknown commit '{}'", commit));
+        self.tags.push((tag_name.to_string(), id));
+    }
+
+    /// Point HEAD at the named commit.
+    ///
+    /// # Panics
+    /// If the commit hasn't been added.
+    pub fn set_head(&mut self, commit: &str) {
+        let id = self
+            .id(commit)
+            .unwrap_or_else(|| panic!("Unknown commit '{}'", commit));
+        self.head = Some(id);
+    }
+
+    /// The id of the named commit.
+    pub fn id(&self, name: &str) -> Option<gix::ObjectId> {
+        self.ids.get(name).copied()
+    }
+}
+
+impl CommitGraph for MemoryGraph {
+    /// A revision is a commit name, a tag name or a full commit id.
+    fn resolve(&self, revision: Option<&str>) -> Result<Option<gix::ObjectId>> {
+        let Some(revision) = revision else {
+            return Ok(self.head);
+        };
+
+        self.id(revision)
+            .or_else(|| {
+                self.tags
+                    .iter()
+                    .find(|(name, _)| name == revision)
+                    .map(|(_, id)| *id)
+            })
+            .or_else(|| {
+                gix::ObjectId::from_hex(revision.as_bytes())
+                    .ok()
+                    .filter(|id| self.commits.contains_key(id))
+            })
+            .map(Some)
+            .ok_or_else(|| TagVerError::InvalidRevision(format!("{}: unknown commit", revision)))
+    }
+
+    fn parents(&self, id: gix::ObjectId) -> Option<Vec<gix::ObjectId>> {
+        self.commits.get(&id).map(|(parents, _)| parents.clone())
+    }
+
+    fn commit_info(&self, id: gix::ObjectId) -> Option<CommitInfo> {
+        self.commits.get(&id).map(|(_, info)| info.clone())
+    }
+}
+
+impl TagSource for MemoryGraph {
+    /// Tags are lightweight, so every tag is skipped when annotated tags are required.
+    fn version_tags(&self, config: &Config) -> Result<TagMap> {
+        let mut tag_map = TagMap::new();
+        let mut invalid = 0;
+
+        for (tag_name, id) in &self.tags {
+            let version = match tags::tag_version(tag_name, config) {
+                Ok(version) => version,
+                Err(reason) => {
+                    invalid += usize::from(reason == SkipReason::InvalidVersion);
+                    tags::log_skipped(tag_name, &reason);
+                    continue;
+                }
+            };
+            if config.tag_requirement != TagRequirement::Any {
+                tags::log_skipped(tag_name, &SkipReason::Lightweight);
+                continue;
+            }
+
+            tag_map.entry(*id).or_default().push(VersionTag {
+                version,
+                tag_name: tag_name.clone(),
+                tagger_time: None,
+            });
+        }
+
+        tracing::Span::current().record("invalid", invalid);
+        tags::sort_tag_map(&mut tag_map, config.tag_selection);
+        Ok(tag_map)
+    }
+}
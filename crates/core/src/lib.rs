@@ -22,15 +22,31 @@
 //! ## Strict vs. fallback entry points
 //! - [`calculate_version`] — requires a real Git repository and errors otherwise.
 //! - [`calculate_version_with_fallback`] — returns the default version when no repository is found.
+//!
+//! ## Logging
+//! The library emits [`tracing`] events and spans but installs no subscriber; use
+//! [`Verbosity::level`] for the level to show. A calculation runs in a `calculate_version`
+//! span, with `discover`, `parse_tags` (fields `tags`, `invalid`) and `walk` (fields `base`,
+//! `height`) spans inside it, each recording `elapsed_ms`. Skipped tags and the chosen base
+//! tag are debug events.
 
+pub mod cache;
 pub mod config;
 pub mod error;
 pub mod git;
+pub mod git_cli;
+pub mod graph;
+pub mod metadata;
+pub mod next;
+#[cfg(feature = "remote-tags")]
+pub mod remote;
+pub mod requirement;
+pub mod signing;
 pub mod tags;
 pub mod version;
 
 pub use config::{Config, Verbosity, VersionPart};
-pub use error::{Result, TagVerError};
+pub use error::{ErrorKind, Result, TagVerError};
 pub use git::Repository;
 pub use version::Version;
 
@@ -53,22 +69,61 @@ pub use version::Version;
 /// - [`TagVerError::GitRepoNotFound`] if the path is not inside a Git repository.
 /// - [`TagVerError::GitCommand`] or [`TagVerError::Other`] for underlying Git failures.
 /// - [`TagVerError::InvalidSemver`] if tags contain invalid SemVer.
+/// - [`TagVerError::InvalidHeightFormat`] if the config is rejected by [`Config::validate`].
+/// - [`TagVerError::NoCommits`] or [`TagVerError::ShallowRepo`] with `config.strict`.
 pub fn calculate_version(
     work_dir: impl Into<std::path::PathBuf>,
     config: &Config,
 ) -> Result<CalculationResult> {
     let work_dir = work_dir.into();
+    let _span = tracing::info_span!(
+        "calculate_version",
+        work_dir = %work_dir.display(),
+        backend = ?config.backend,
+    )
+    .entered();
+    config.validate()?;
+
+    if config.backend == config::Backend::Git {
+        return calculate_version_with_git(work_dir, config);
+    }
 
     // Try to discover the repository
-    let repo = Repository::discover(&work_dir)?;
+    let repo = open_repository(&work_dir, config)?;
 
-    // Check for shallow clone warning
-    if repo.is_shallow() {
-        tracing::warn!("Shallow repository detected. Version calculation may be incorrect. Fetch full history with 'git fetch --unshallow'.");
-    }
+    check_shallow(repo.is_shallow(), config)?;
 
     // Calculate the version
-    let (version, height, is_from_tag) = git::calculate_version(&repo, config)?;
+    let (version, height, is_from_tag) = if config.cache {
+        cache::calculate_version(&repo, config)?
+    } else {
+        git::calculate_version(&repo, config)?
+    };
+
+    Ok(CalculationResult {
+        version,
+        height,
+        is_from_tag,
+        work_dir,
+    })
+}
+
+/// [`calculate_version`] with the `git` executable; see [`git_cli`].
+fn calculate_version_with_git(
+    work_dir: std::path::PathBuf,
+    config: &Config,
+) -> Result<CalculationResult> {
+    let git = git_cli::GitCli::discover(&work_dir)?.with_config(config);
+    check_shallow(git.is_shallow(), config)?;
+    #[cfg(feature = "remote-tags")]
+    let remote_tags = config.remote_tags.is_some();
+    #[cfg(not(feature = "remote-tags"))]
+    let remote_tags = false;
+    if config.cache || remote_tags || config.use_superproject {
+        tracing::warn!("The git backend ignores the cache, remote tags and superproject options.");
+    }
+
+    let (version, height, is_from_tag) = git::calculate_version_with(&git, &git, config)?;
 
     Ok(CalculationResult {
         version,
@@ -78,6 +133,72 @@ pub fn calculate_version(
     })
 }
 
+/// List every tag with the diagnostics needed to audit a tag namespace: the parsed
+/// version, target commit, reachability from HEAD and why a tag is skipped.
+///
+/// # Errors
+/// - [`TagVerError::GitRepoNotFound`] if the path is not inside a Git repository.
+/// - [`TagVerError::InvalidRevision`] if `config.revision` doesn't name a commit.
+/// - [`TagVerError::InvalidHeightFormat`] if the config is rejected by [`Config::validate`].
+pub fn list_tags(
+    work_dir: impl Into<std::path::PathBuf>,
+    config: &Config,
+) -> Result<Vec<tags::TagInfo>> {
+    config.validate()?;
+    let repo = open_repository(&work_dir.into(), config)?;
+    tags::list_tags(repo.inner(), config)
+}
+
+/// Preview the next version for each kind of bump from the base tag, and the bump
+/// Conventional Commits call for; see [`next`].
+///
+/// # Examples
+/// ```rust,no_run
+/// use tagver::next::BumpKind;
+/// use tagver::{next_versions, Config};
+///
+/// let next = next_versions(".", &Config::default())?;
+/// println!("{} -> {}", next.base, next.get(BumpKind::Minor));
+/// # Ok::<_, tagver::TagVerError>(())
+/// ```
+///
+/// # Errors
+/// - [`TagVerError::GitRepoNotFound`] if the path is not inside a Git repository.
+/// - [`TagVerError::InvalidRevision`] if `config.revision` doesn't name a commit.
+/// - [`TagVerError::VersionOverflow`] if a bump overflows.
+/// - [`TagVerError::InvalidHeightFormat`] if the config is rejected by [`Config::validate`].
+pub fn next_versions(
+    work_dir: impl Into<std::path::PathBuf>,
+    config: &Config,
+) -> Result<next::NextVersions> {
+    config.validate()?;
+    let work_dir = work_dir.into();
+
+    if config.backend == config::Backend::Git {
+        let git = git_cli::GitCli::discover(&work_dir)?.with_config(config);
+        return next::next_versions_with(&git, &git, config);
+    }
+
+    let repo = open_repository(&work_dir, config)?;
+    let graph = git::GixGraph::new(repo.inner(), config);
+    next::next_versions_with(&graph, repo.inner(), config)
+}
+
+/// Warn about a shallow clone, or fail in strict mode.
+fn check_shallow(is_shallow: bool, config: &Config) -> Result<()> {
+    if is_shallow {
+        if config.strict {
+            return Err(TagVerError::ShallowRepo);
+        }
+        tracing::warn!("Shallow repository detected. Version calculation may be incorrect. Fetch full history with 'git fetch --unshallow'.");
+    }
+    Ok(())
+}
+
+fn open_repository(work_dir: &std::path::Path, config: &Config) -> Result<Repository> {
+    git::prepare_repository(Repository::discover(work_dir)?, config)
+}
+
 /// Calculate the version, falling back to the default version when no repository is found.
 ///
 /// # Examples
@@ -99,6 +220,13 @@ pub fn calculate_version_with_fallback(
     config: &Config,
 ) -> Result<CalculationResult> {
     let work_dir = work_dir.into();
+    let _span = tracing::info_span!(
+        "calculate_version",
+        work_dir = %work_dir.display(),
+        backend = ?config.backend,
+    )
+    .entered();
+    config.validate()?;
 
     // Try to discover and calculate version
     let (version, height, is_from_tag) = git::calculate_version_fallback(&work_dir, config)?;
@@ -131,6 +259,43 @@ pub struct CalculationResult {
     pub work_dir: std::path::PathBuf,
 }
 
+impl CalculationResult {
+    /// Whether the version is in `req`, with pre-releases matched as `prerelease` says;
+    /// see [`requirement`].
+    ///
+    /// # Examples
+    /// ```rust
+    /// use tagver::requirement::PrereleaseMatching;
+    /// use tagver::{calculate_version_with_fallback, Config};
+    ///
+    /// let result = calculate_version_with_fallback("/tmp/not-a-repo-tagver", &Config::default())?;
+    /// let req = "<1".parse().unwrap();
+    /// assert!(!result.satisfies(&req, PrereleaseMatching::Strict));
+    /// assert!(result.satisfies(&req, PrereleaseMatching::Include));
+    /// # Ok::<_, tagver::TagVerError>(())
+    /// ```
+    pub fn satisfies(
+        &self,
+        req: &semver::VersionReq,
+        prerelease: requirement::PrereleaseMatching,
+    ) -> bool {
+        let requirement = requirement::Requirement {
+            version_req: Some(req.clone()),
+            require_stable: false,
+            prerelease,
+        };
+        requirement.check(&self.version).is_ok()
+    }
+
+    /// Check the version against `requirement`.
+    ///
+    /// # Errors
+    /// [`TagVerError::RequirementNotMet`] with the reason if the version doesn't satisfy it.
+    pub fn check(&self, requirement: &requirement::Requirement) -> Result<()> {
+        requirement.check(&self.version)
+    }
+}
+
 impl std::fmt::Display for CalculationResult {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.version)
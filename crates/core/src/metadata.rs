@@ -0,0 +1,277 @@
+//! Build metadata templates composed from repository and CI state.
+//!
+//! A template is literal build metadata with optional placeholders:
+//!
+//! | Placeholder | Value |
+//! |-------------|-------|
+//! | `{sha}` | Full id of the versioned commit |
+//! | `{sha7}` | Versioned commit id, abbreviated to 7 characters |
+//! | `{commit_date}` | Versioned commit date in UTC, as `YYYYMMDD` |
+//! | `{ci_build}` | First of [`CI_BUILD_VARIABLES`] that is set |
+//! | `{branch}` | Current branch name, or the branch named by the configured revision |
+//! | `{dirty}` | `dirty` when tracked files have uncommitted changes; empty with a configured revision |
+//!
+//! Substituted values are sanitized to SemVer build identifiers, and identifiers left empty
+//! by an unavailable value are dropped. Literal text is validated with the rest of the
+//! version, according to [`Config::identifier_validation`](crate::Config).
+
+use std::str::FromStr;
+
+use crate::error::{Result, TagVerError};
+use crate::graph::CommitGraph;
+use crate::version::sanitize_identifier;
+
+/// Environment variables checked, in order, for a CI build number.
+pub const CI_BUILD_VARIABLES: &[&str] = &["GITHUB_RUN_NUMBER", "CI_PIPELINE_IID", "BUILD_NUMBER"];
+
+/// Named build metadata templates.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MetadataPreset {
+    /// `{ci_build}.{sha7}`
+    Ci,
+    /// `{sha7}`
+    Sha,
+    /// `{sha7}.{dirty}`
+    Local,
+}
+
+impl MetadataPreset {
+    /// The template this preset expands to.
+    pub fn template(&self) -> &'static str {
+        match self {
+            MetadataPreset::Ci => "{ci_build}.{sha7}",
+            MetadataPreset::Sha => "{sha7}",
+            MetadataPreset::Local => "{sha7}.{dirty}",
+        }
+    }
+}
+
+impl FromStr for MetadataPreset {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "ci" => Ok(MetadataPreset::Ci),
+            "sha" => Ok(MetadataPreset::Sha),
+            "local" => Ok(MetadataPreset::Local),
+            _ => Err(format!("Invalid metadata preset: {}", s)),
+        }
+    }
+}
+
+/// Values substituted into a build metadata template.
+#[derive(Debug, Clone, Default)]
+pub struct MetadataContext {
+    pub sha: Option<String>,
+    pub commit_date: Option<String>,
+    pub ci_build: Option<String>,
+    pub branch: Option<String>,
+    pub dirty: bool,
+}
+
+impl MetadataContext {
+    /// Collect the values for the placeholders used in `template`.
+    ///
+    /// `commit` is the versioned commit and `revision` the revision it was resolved from,
+    /// if not HEAD. Only the values the template refers to are looked up, so a plain
+    /// template never touches the repository.
+    pub fn collect(
+        template: &str,
+        repo: Option<&gix::Repository>,
+        commit: Option<gix::ObjectId>,
+        revision: Option<&str>,
+    ) -> Result<Self> {
+        let placeholders = placeholders(template);
+        let uses = |name: &str| placeholders.iter().any(|p| p == name);
+        let mut context = MetadataContext::default();
+
+        if uses("ci_build") {
+            context.ci_build = ci_build();
+        }
+
+        let Some(repo) = repo else {
+            return Ok(context);
+        };
+
+        if uses("sha") || uses("sha7") {
+            context.sha = commit.map(|id| id.to_string());
+        }
+
+        if uses("commit_date") {
+            if let Some(id) = commit {
+                let time = repo
+                    .find_commit(id)
+                    .map_err(|e| {
+                        TagVerError::Other(format!("Failed to find target commit: {}", e))
+                    })?
+                    .time()
+                    .map_err(|e| {
+                        TagVerError::Other(format!("Failed to read target commit time: {}", e))
+                    })?;
+                context.commit_date = Some(format_time(time.seconds, "%Y%m%d"));
+            }
+        }
+
+        if uses("branch") {
+            let name = match revision {
+                Some(revision) => repo
+                    .try_find_reference(revision)
+                    .ok()
+                    .flatten()
+                    .map(|reference| reference.name().to_owned()),
+                None => repo.head_name().ok().flatten(),
+            };
+            context.branch = name
+                .filter(|name| {
+                    matches!(
+                        name.category(),
+                        Some(gix::refs::Category::LocalBranch | gix::refs::Category::RemoteBranch)
+                    )
+                })
+                .map(|name| name.shorten().to_string());
+        }
+
+        // The working tree only describes HEAD, not another revision
+        if uses("dirty") && revision.is_none() && repo.workdir().is_some() {
+            context.dirty = repo.is_dirty().map_err(|e| {
+                TagVerError::Other(format!("Failed to check working tree status: {}", e))
+            })?;
+        }
+
+        Ok(context)
+    }
+
+    /// Collect the values for the placeholders used in `template` from a commit graph,
+    /// which provides the commit id and date but no branch or working tree.
+    pub fn collect_from_graph(
+        template: &str,
+        graph: &dyn CommitGraph,
+        commit: Option<gix::ObjectId>,
+    ) -> Self {
+        let placeholders = placeholders(template);
+        let uses = |name: &str| placeholders.iter().any(|p| p == name);
+
+        MetadataContext {
+            sha: commit.map(|id| id.to_string()),
+            commit_date: commit
+                .filter(|_| uses("commit_date"))
+                .and_then(|id| graph.commit_info(id))
+                .map(|info| format_time(info.time, "%Y%m%d")),
+            ci_build: ci_build(),
+            ..Default::default()
+        }
+    }
+
+    fn value(&self, placeholder: &str) -> Option<String> {
+        match placeholder {
+            "sha" => self.sha.clone(),
+            "sha7" => self.sha.as_ref().map(|sha| sha.chars().take(7).collect()),
+            "commit_date" => self.commit_date.clone(),
+            "ci_build" => self.ci_build.clone(),
+            "branch" => self.branch.clone(),
+            "dirty" => self.dirty.then(|| "dirty".to_string()),
+            _ => None,
+        }
+    }
+}
+
+/// The first CI build number of [`CI_BUILD_VARIABLES`] that is set.
+fn ci_build() -> Option<String> {
+    CI_BUILD_VARIABLES
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|value| !value.is_empty())
+}
+
+/// Format a time in seconds since the Unix epoch in UTC, so the result doesn't depend on
+/// the committer's timezone.
+pub(crate) fn format_time(seconds: i64, format: &'static str) -> String {
+    gix::date::Time::new(seconds, 0).format_or_unix(gix::date::time::CustomFormat::new(format))
+}
+
+/// Expand a template into SemVer build metadata.
+///
+/// Identifiers emptied by their placeholders are dropped, and `None` is returned when none
+/// are left. Empty literal identifiers, as in `a..b`, are kept for validation to reject.
+///
+/// # Examples
+/// ```rust
+/// use tagver::metadata::{expand, MetadataContext};
+///
+/// let context = MetadataContext {
+///     sha: Some("0123456789abcdef".into()),
+///     branch: Some("feature/x y".into()),
+///     ..Default::default()
+/// };
+/// let metadata = expand("{branch}.{sha7}.{ci_build}", &context)?;
+/// assert_eq!(metadata.as_deref(), Some("feature-x-y.0123456"));
+/// # Ok::<_, tagver::TagVerError>(())
+/// ```
+///
+/// # Errors
+/// [`TagVerError::InvalidBuildMetadata`] for unknown or unclosed placeholders.
+pub fn expand(template: &str, context: &MetadataContext) -> Result<Option<String>> {
+    let mut identifiers = Vec::new();
+    for identifier in template.split('.') {
+        let expanded = expand_identifier(identifier, template, context)?;
+        if expanded.is_empty() && !identifier.is_empty() {
+            continue;
+        }
+        identifiers.push(expanded);
+    }
+
+    let metadata = identifiers.join(".");
+    if metadata.is_empty() {
+        Ok(None)
+    } else {
+        Ok(Some(metadata))
+    }
+}
+
+/// Substitute the placeholders in one identifier of `template`.
+fn expand_identifier(
+    identifier: &str,
+    template: &str,
+    context: &MetadataContext,
+) -> Result<String> {
+    let mut expanded = String::new();
+    let mut rest = identifier;
+
+    while let Some(start) = rest.find('{') {
+        expanded.push_str(&rest[..start]);
+        let end = rest[start..].find('}').ok_or_else(|| {
+            TagVerError::InvalidBuildMetadata(format!("Unclosed placeholder in '{}'", template))
+        })? + start;
+        let name = &rest[start + 1..end];
+        if !PLACEHOLDERS.contains(&name) {
+            return Err(TagVerError::InvalidBuildMetadata(format!(
+                "Unknown placeholder '{{{}}}' in '{}'",
+                name, template
+            )));
+        }
+        if let Some(value) = context.value(name) {
+            expanded.push_str(&sanitize_identifier(&value));
+        }
+        rest = &rest[end + 1..];
+    }
+    expanded.push_str(rest);
+
+    Ok(expanded)
+}
+
+const PLACEHOLDERS: &[&str] = &["sha", "sha7", "commit_date", "ci_build", "branch", "dirty"];
+
+fn placeholders(template: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        let Some(end) = rest[start..].find('}') else {
+            break; // Reported by `expand`
+        };
+        names.push(rest[start + 1..start + end].to_string());
+        rest = &rest[start + end + 1..];
+    }
+
+    names
+}
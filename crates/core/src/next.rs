@@ -0,0 +1,176 @@
+//! Previews of the next release version for each kind of bump.
+//!
+//! [`next_versions`](crate::next_versions) starts from the base tag the version calculation
+//! finds, and also reports which bump the commits since that tag call for under
+//! [Conventional Commits](https://www.conventionalcommits.org).
+
+use std::str::FromStr;
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::config::{Config, VersionPart};
+use crate::error::Result;
+use crate::git;
+use crate::graph::{CommitGraph, TagSource};
+use crate::version::Version;
+
+/// A kind of version bump.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BumpKind {
+    Prerelease,
+    Patch,
+    Minor,
+    Major,
+}
+
+impl FromStr for BumpKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "major" => Ok(BumpKind::Major),
+            "minor" => Ok(BumpKind::Minor),
+            "patch" => Ok(BumpKind::Patch),
+            "prerelease" | "pre" => Ok(BumpKind::Prerelease),
+            _ => Err(format!("Invalid bump kind: {}", s)),
+        }
+    }
+}
+
+impl std::fmt::Display for BumpKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            BumpKind::Major => "major",
+            BumpKind::Minor => "minor",
+            BumpKind::Patch => "patch",
+            BumpKind::Prerelease => "prerelease",
+        })
+    }
+}
+
+/// The next version for each kind of bump.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct NextVersions {
+    /// The base tag, or `None` when no version tag is reachable.
+    pub base_tag: Option<String>,
+    /// The version of the base tag, or `0.0.0` without one.
+    pub base: Version,
+    /// Commits since the base tag, by `config.height_metric`.
+    pub height: u32,
+    pub major: Version,
+    pub minor: Version,
+    pub patch: Version,
+    pub prerelease: Version,
+    /// The bump Conventional Commits call for, or `None` without commits since the tag.
+    pub conventional: Option<BumpKind>,
+}
+
+impl NextVersions {
+    /// The next version for `kind`.
+    pub fn get(&self, kind: BumpKind) -> &Version {
+        match kind {
+            BumpKind::Major => &self.major,
+            BumpKind::Minor => &self.minor,
+            BumpKind::Patch => &self.patch,
+            BumpKind::Prerelease => &self.prerelease,
+        }
+    }
+}
+
+/// The next versions from the base tag of HEAD (or `config.revision`) in any commit graph.
+///
+/// The prerelease bump of a release starts the pre-releases of the next version with
+/// `config.auto_increment` and `config.default_prerelease_identifiers`.
+///
+/// # Errors
+/// - [`TagVerError::InvalidRevision`](crate::TagVerError) if `config.revision` doesn't name
+///   a commit.
+/// - [`TagVerError::VersionOverflow`](crate::TagVerError) if a bump overflows.
+pub fn next_versions_with(
+    graph: &impl CommitGraph,
+    tags: &impl TagSource,
+    config: &Config,
+) -> Result<NextVersions> {
+    let tag_map = git::version_tags(tags, config)?;
+    let head = graph.resolve(config.revision.as_deref())?;
+
+    let (base_tag, height, conventional) = match head {
+        Some(head) => {
+            let (base_tag, height) = git::find_base_tag(graph, head, &tag_map, config);
+            let base_commit = base_tag.as_ref().and_then(|tag| {
+                tag_map
+                    .iter()
+                    .find(|(_, tags)| tags.iter().any(|t| t.tag_name == tag.tag_name))
+                    .map(|(id, _)| *id)
+            });
+            let messages: Vec<String> = graph
+                .reachable(&[head], base_commit)
+                .into_iter()
+                .filter_map(|(id, _)| graph.commit_info(id))
+                .map(|info| info.message)
+                .collect();
+            let conventional = conventional_bump(messages.iter().map(String::as_str));
+            (base_tag, height, conventional)
+        }
+        None => (None, 0, None),
+    };
+
+    let base = base_tag
+        .as_ref()
+        .map(|tag| Version {
+            build_metadata: None,
+            ..tag.version.clone()
+        })
+        .unwrap_or_else(|| Version::new(0, 0, 0));
+
+    Ok(NextVersions {
+        major: base.next_release(&VersionPart::Major)?,
+        minor: base.next_release(&VersionPart::Minor)?,
+        patch: base.next_release(&VersionPart::Patch)?,
+        prerelease: base.increment_prerelease(
+            &config.auto_increment,
+            &config.default_prerelease_identifiers,
+        )?,
+        base_tag: base_tag.map(|tag| tag.tag_name),
+        base,
+        height,
+        conventional,
+    })
+}
+
+static HEADER: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^(?<type>[A-Za-z]+)(\([^()]*\))?(?<breaking>!)?: ").unwrap());
+
+/// The bump a commit message calls for under Conventional Commits: major for a breaking
+/// change (`feat!:` or a `BREAKING CHANGE:` footer), minor for `feat`, and patch for any
+/// other commit, conventional or not.
+pub fn commit_bump(message: &str) -> BumpKind {
+    let breaking_footer = message
+        .lines()
+        .skip(1)
+        .any(|line| line.starts_with("BREAKING CHANGE:") || line.starts_with("BREAKING-CHANGE:"));
+    let header = HEADER.captures(message.lines().next().unwrap_or_default());
+
+    match header {
+        _ if breaking_footer => BumpKind::Major,
+        Some(header) if header.name("breaking").is_some() => BumpKind::Major,
+        Some(header) if header["type"].eq_ignore_ascii_case("feat") => BumpKind::Minor,
+        _ => BumpKind::Patch,
+    }
+}
+
+/// The largest bump the commit messages call for, or `None` without commits.
+///
+/// # Examples
+/// ```rust
+/// use tagver::next::{conventional_bump, BumpKind};
+///
+/// let bump = conventional_bump(["fix: typo", "feat(cli): add next", "docs: readme"]);
+/// assert_eq!(bump, Some(BumpKind::Minor));
+/// assert_eq!(conventional_bump(["refactor!: drop v1 API"]), Some(BumpKind::Major));
+/// ```
+pub fn conventional_bump<'a>(messages: impl IntoIterator<Item = &'a str>) -> Option<BumpKind> {
+    messages.into_iter().map(commit_bump).max()
+}
@@ -0,0 +1,138 @@
+//! Tags that exist only on a remote.
+//!
+//! CI checkouts often fetch branches without tags. With `config.remote_tags` set, the
+//! remote's tags are listed from its ref advertisement before the calculation. A tag is
+//! fetched into the local `refs/tags` when it yields a version, doesn't exist locally yet
+//! and points to a commit that is already present, so no history is downloaded. Objects
+//! of annotated tags are fetched along with the refs; local tags are never overwritten.
+
+use std::sync::atomic::AtomicBool;
+
+use crate::config::Config;
+use crate::error::{Result, TagVerError};
+use crate::tags;
+
+/// Refspec used to list the remote's tags.
+const TAGS_REFSPEC: &str = "refs/tags/*:refs/tags/*";
+
+/// Fetch the version tags of `remote`, a configured remote name or URL, whose commits
+/// exist locally. Returns the names of the fetched tags.
+pub fn fetch_tags(repo: &gix::Repository, remote: &str, config: &Config) -> Result<Vec<String>> {
+    let refs = list_remote_tags(repo, remote)?;
+
+    let mut wanted = Vec::new();
+    for (name, commit) in refs {
+        let Some(tag_name) = name.strip_prefix("refs/tags/") else {
+            continue;
+        };
+        if tags::version_text(tag_name, config).is_err() {
+            continue;
+        }
+        if repo
+            .try_find_reference(name.as_str())
+            .ok()
+            .flatten()
+            .is_some()
+        {
+            continue;
+        }
+        if !repo.has_object(commit) {
+            tracing::debug!(
+                "Ignoring remote tag '{}': commit {} is not available locally",
+                tag_name,
+                commit
+            );
+            continue;
+        }
+        wanted.push(name);
+    }
+
+    if wanted.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    // Non-forced refspecs never move an existing tag
+    let refspecs: Vec<String> = wanted
+        .iter()
+        .map(|name| format!("{}:{}", name, name))
+        .collect();
+    detached_remote(repo, remote, &refspecs)?
+        .connect(gix::remote::Direction::Fetch)
+        .map_err(|e| remote_error(remote, e))?
+        .prepare_fetch(gix::progress::Discard, Default::default())
+        .map_err(|e| remote_error(remote, e))?
+        .receive(gix::progress::Discard, &AtomicBool::new(false))
+        .map_err(|e| remote_error(remote, e))?;
+
+    let fetched: Vec<String> = wanted
+        .into_iter()
+        .map(|name| name.trim_start_matches("refs/tags/").to_string())
+        .collect();
+    tracing::debug!("Fetched {} tags from '{}'", fetched.len(), remote);
+    Ok(fetched)
+}
+
+/// The full names of the remote's tags with the commits they peel to.
+fn list_remote_tags(repo: &gix::Repository, remote: &str) -> Result<Vec<(String, gix::ObjectId)>> {
+    let (ref_map, _handshake) = detached_remote(repo, remote, &[TAGS_REFSPEC])?
+        .connect(gix::remote::Direction::Fetch)
+        .map_err(|e| remote_error(remote, e))?
+        .ref_map(gix::progress::Discard, Default::default())
+        .map_err(|e| remote_error(remote, e))?;
+
+    let refs = ref_map
+        .remote_refs
+        .iter()
+        .filter_map(|remote_ref| {
+            use gix::protocol::handshake::Ref;
+            match remote_ref {
+                Ref::Peeled {
+                    full_ref_name,
+                    object,
+                    ..
+                }
+                | Ref::Direct {
+                    full_ref_name,
+                    object,
+                }
+                | Ref::Symbolic {
+                    full_ref_name,
+                    object,
+                    ..
+                } => Some((full_ref_name.to_string(), *object)),
+                Ref::Unborn { .. } => None,
+            }
+        })
+        .filter(|(name, _)| name.starts_with("refs/tags/"))
+        .collect();
+
+    Ok(refs)
+}
+
+/// A remote for the fetch URL of `remote` with only the given refspecs, so the configured
+/// refspecs and tag options don't apply.
+fn detached_remote<'repo>(
+    repo: &'repo gix::Repository,
+    remote: &str,
+    refspecs: &[impl AsRef<str>],
+) -> Result<gix::Remote<'repo>> {
+    let url = repo
+        .find_fetch_remote(Some(remote.into()))
+        .map_err(|e| remote_error(remote, e))?
+        .url(gix::remote::Direction::Fetch)
+        .cloned()
+        .ok_or_else(|| remote_error(remote, "no fetch URL configured"))?;
+
+    repo.remote_at(url)
+        .map_err(|e| remote_error(remote, e))?
+        .with_fetch_tags(gix::remote::fetch::Tags::None)
+        .with_refspecs(
+            refspecs.iter().map(|spec| spec.as_ref()),
+            gix::remote::Direction::Fetch,
+        )
+        .map_err(|e| remote_error(remote, e))
+}
+
+fn remote_error(remote: &str, error: impl std::fmt::Display) -> TagVerError {
+    TagVerError::RemoteTags(format!("{}: {}", remote, error))
+}
@@ -0,0 +1,195 @@
+//! Checks that a calculated version satisfies a version requirement, to gate deploys on
+//! rules such as `>=2.0.0, <3` or "must be a stable release".
+//!
+//! Requirements are [`semver::VersionReq`]s. With [`PrereleaseMatching::Strict`] they match
+//! like Cargo: a pre-release only matches a comparator naming a pre-release of the same
+//! `major.minor.patch`, so `2.1.0-alpha.0.3` doesn't match `>=2.0.0`. With
+//! [`PrereleaseMatching::Include`], pre-releases are compared by precedence like
+//! node-semver's `includePrerelease`: `2.1.0-alpha.0.3` matches `>=2.0.0, <3`. A partial
+//! upper bound excludes its pre-releases, so `3.0.0-rc.1` doesn't match `<3`, while a full
+//! one doesn't: `3.0.0-rc.1` matches `<3.0.0`, as it precedes `3.0.0`.
+
+pub use semver::VersionReq;
+use semver::{BuildMetadata, Comparator, Op, Prerelease};
+
+use crate::error::{Result, TagVerError};
+use crate::version::Version;
+
+/// How pre-release versions match a [`VersionReq`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PrereleaseMatching {
+    /// Cargo's rules: only comparators naming a pre-release of the same version match one.
+    #[default]
+    Strict,
+    /// Pre-releases are compared by precedence, excluding those of partial upper bounds.
+    Include,
+}
+
+/// What a version must satisfy.
+///
+/// # Examples
+/// ```rust
+/// use tagver::requirement::{PrereleaseMatching, Requirement};
+/// use tagver::Version;
+///
+/// let requirement = Requirement {
+///     version_req: Some(">=2.0.0, <3".parse().unwrap()),
+///     require_stable: false,
+///     prerelease: PrereleaseMatching::Include,
+/// };
+/// assert!(requirement.check(&Version::parse("2.1.0-alpha.0.3")?).is_ok());
+/// assert!(requirement.check(&Version::parse("3.0.0-rc.1")?).is_err());
+/// # Ok::<_, tagver::TagVerError>(())
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Requirement {
+    /// Version range the version must be in, if any.
+    pub version_req: Option<VersionReq>,
+    /// Reject pre-releases.
+    pub require_stable: bool,
+    pub prerelease: PrereleaseMatching,
+}
+
+impl Requirement {
+    /// Check `version` against the requirement; build metadata is ignored.
+    ///
+    /// # Errors
+    /// - [`TagVerError::RequirementNotMet`] with the reason if the version doesn't satisfy it.
+    /// - [`TagVerError::InvalidSemver`] if the version has identifiers that aren't valid SemVer.
+    pub fn check(&self, version: &Version) -> Result<()> {
+        if self.require_stable && version.is_prerelease() {
+            return Err(TagVerError::RequirementNotMet(format!(
+                "{} is a pre-release, but a stable release is required",
+                version
+            )));
+        }
+
+        let Some(req) = &self.version_req else {
+            return Ok(());
+        };
+        let semver = to_semver(version)?;
+        if matches(req, &semver, self.prerelease) {
+            return Ok(());
+        }
+
+        let hint = if self.prerelease == PrereleaseMatching::Strict
+            && matches(req, &semver, PrereleaseMatching::Include)
+        {
+            "; pre-releases only match comparators with a pre-release of the same version, \
+             include pre-releases to compare them by precedence"
+        } else {
+            ""
+        };
+        Err(TagVerError::RequirementNotMet(format!(
+            "{} does not satisfy '{}'{}",
+            version, req, hint
+        )))
+    }
+}
+
+/// Whether `version` satisfies `req` under the given pre-release matching.
+///
+/// # Examples
+/// ```rust
+/// use tagver::requirement::{matches, PrereleaseMatching};
+///
+/// let req = ">=2.0.0".parse().unwrap();
+/// let version = "2.1.0-alpha.0.3".parse().unwrap();
+/// assert!(!matches(&req, &version, PrereleaseMatching::Strict));
+/// assert!(matches(&req, &version, PrereleaseMatching::Include));
+/// ```
+pub fn matches(
+    req: &VersionReq,
+    version: &semver::Version,
+    prerelease: PrereleaseMatching,
+) -> bool {
+    let version = semver::Version {
+        build: BuildMetadata::EMPTY,
+        ..version.clone()
+    };
+    match prerelease {
+        PrereleaseMatching::Strict => req.matches(&version),
+        PrereleaseMatching::Include => req
+            .comparators
+            .iter()
+            .all(|comparator| matches_by_precedence(comparator, &version)),
+    }
+}
+
+/// Whether `version` satisfies `comparator` by precedence, with node-semver's bounds:
+/// a partial comparator such as `1.2` covers `1.2.0-0` up to `1.3.0-0`, so a partial
+/// exclusive upper bound excludes its pre-releases (`<3` is `<3.0.0-0`, `<3.0.0` isn't).
+fn matches_by_precedence(comparator: &Comparator, version: &semver::Version) -> bool {
+    let major = comparator.major;
+    let is_full = comparator.minor.is_some() && comparator.patch.is_some();
+    let minor = comparator.minor.unwrap_or(0);
+    let patch = comparator.patch.unwrap_or(0);
+
+    // The lowest version the comparator names, e.g. 1.2.0-0 for 1.2
+    let lower = if is_full {
+        with_pre(major, minor, patch, comparator.pre.clone())
+    } else {
+        lowest(major, minor, patch)
+    };
+    // The lowest version above a partial comparator, e.g. 1.3.0-0 for 1.2
+    let above = match (comparator.minor, comparator.patch) {
+        (None, _) => lowest(major.saturating_add(1), 0, 0),
+        (Some(minor), None) => lowest(major, minor.saturating_add(1), 0),
+        (Some(minor), Some(patch)) => lowest(major, minor, patch.saturating_add(1)),
+    };
+
+    match comparator.op {
+        Op::Exact | Op::Wildcard if is_full => *version == lower,
+        Op::Exact | Op::Wildcard => lower <= *version && *version < above,
+        Op::Greater if is_full => *version > lower,
+        Op::Greater => *version >= above,
+        Op::GreaterEq => *version >= lower,
+        Op::Less => *version < lower,
+        Op::LessEq if is_full => *version <= lower,
+        Op::LessEq => *version < above,
+        Op::Tilde => {
+            let upper = match comparator.minor {
+                Some(minor) => lowest(major, minor.saturating_add(1), 0),
+                None => lowest(major.saturating_add(1), 0, 0),
+            };
+            lower <= *version && *version < upper
+        }
+        Op::Caret => {
+            let upper = match (major, comparator.minor, comparator.patch) {
+                (0, None, _) => lowest(1, 0, 0),
+                (0, Some(0), None) => lowest(0, 1, 0),
+                (0, Some(0), Some(patch)) => lowest(0, 0, patch.saturating_add(1)),
+                (0, Some(minor), _) => lowest(0, minor.saturating_add(1), 0),
+                (major, _, _) => lowest(major.saturating_add(1), 0, 0),
+            };
+            lower <= *version && *version < upper
+        }
+        _ => comparator.matches(version),
+    }
+}
+
+fn with_pre(major: u64, minor: u64, patch: u64, pre: Prerelease) -> semver::Version {
+    semver::Version {
+        major,
+        minor,
+        patch,
+        pre,
+        build: BuildMetadata::EMPTY,
+    }
+}
+
+/// The lowest version of `major.minor.patch`, its `-0` pre-release.
+fn lowest(major: u64, minor: u64, patch: u64) -> semver::Version {
+    with_pre(
+        major,
+        minor,
+        patch,
+        Prerelease::new("0").expect("valid pre-release"),
+    )
+}
+
+fn to_semver(version: &Version) -> Result<semver::Version> {
+    let pre = Prerelease::new(&version.prerelease.join("."))
+        .map_err(|e| TagVerError::InvalidSemver(format!("'{}': {}", version, e)))?;
+    Ok(with_pre(version.major, version.minor, version.patch, pre))
+}
@@ -0,0 +1,201 @@
+//! Tag signature detection and SSH signature verification.
+//!
+//! Git appends the signature of a signed tag to the tag message. Everything before the
+//! signature block is the signed payload. SSH signatures are verified locally against an
+//! allowed signers file, the same format as git's `gpg.ssh.allowedSignersFile`:
+//!
+//! ```text
+//! # principals [options] key-type base64-key [comment]
+//! release@example.com namespaces="git" ssh-ed25519 AAAAC3NzaC1lZDI1NTE5AAAAI...
+//! ```
+//!
+//! Entries restricted by `namespaces` must include `git`. Entries with `cert-authority`,
+//! `valid-after` or `valid-before` are not supported and never match.
+
+use std::path::{Path, PathBuf};
+
+use ssh_key::{PublicKey, SshSig};
+
+use crate::config::Config;
+use crate::error::{Result, TagVerError};
+
+/// Namespace git uses for SSH signatures.
+const NAMESPACE: &str = "git";
+
+const SIGNATURE_HEADERS: &[&[u8]] = &[
+    b"-----BEGIN PGP SIGNATURE-----",
+    b"-----BEGIN PGP MESSAGE-----",
+    b"-----BEGIN SSH SIGNATURE-----",
+    b"-----BEGIN SIGNED MESSAGE-----",
+];
+
+/// Split a raw tag object into the signed payload and the signature block.
+///
+/// Like git, the last line starting a signature block is used. Returns `None` for
+/// unsigned tags.
+pub fn split_signature(data: &[u8]) -> Option<(&[u8], &[u8])> {
+    let mut start = None;
+    let mut offset = 0;
+
+    for line in data.split_inclusive(|&b| b == b'\n') {
+        if SIGNATURE_HEADERS
+            .iter()
+            .any(|header| line.starts_with(header))
+        {
+            start = Some(offset);
+        }
+        offset += line.len();
+    }
+
+    start.map(|start| data.split_at(start))
+}
+
+/// Keys trusted to sign tags, read from an allowed signers file.
+#[derive(Debug, Clone)]
+pub struct AllowedSigners {
+    signers: Vec<AllowedSigner>,
+}
+
+#[derive(Debug, Clone)]
+struct AllowedSigner {
+    principals: String,
+    key: PublicKey,
+}
+
+impl AllowedSigners {
+    /// Read and parse an allowed signers file.
+    pub fn read(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path).map_err(|e| {
+            TagVerError::InvalidAllowedSigners(format!("{}: {}", path.display(), e))
+        })?;
+        Self::parse(&text)
+            .map_err(|e| TagVerError::InvalidAllowedSigners(format!("{}: {}", path.display(), e)))
+    }
+
+    /// Parse the contents of an allowed signers file.
+    pub fn parse(text: &str) -> std::result::Result<Self, String> {
+        let mut signers = Vec::new();
+
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let tokens = split_quoted(line, |c| c.is_ascii_whitespace());
+            let Some(key_index) = tokens.iter().position(|token| is_key_type(token)) else {
+                return Err(format!("line {}: missing public key", number + 1));
+            };
+            if key_index == 0 || key_index > 2 || key_index + 1 >= tokens.len() {
+                return Err(format!("line {}: expected principals and key", number + 1));
+            }
+
+            let key = PublicKey::from_openssh(&format!(
+                "{} {}",
+                tokens[key_index],
+                tokens[key_index + 1]
+            ))
+            .map_err(|e| format!("line {}: {}", number + 1, e))?;
+
+            if key_index == 2 && !options_allow_git(&tokens[1]) {
+                continue;
+            }
+
+            signers.push(AllowedSigner {
+                principals: tokens[0].trim_matches('"').to_string(),
+                key,
+            });
+        }
+
+        Ok(Self { signers })
+    }
+
+    /// Verify an SSH signature over `payload`, returning the signer's principals.
+    pub fn verify(&self, payload: &[u8], signature: &[u8]) -> std::result::Result<String, String> {
+        if !signature.starts_with(b"-----BEGIN SSH SIGNATURE-----") {
+            return Err("only SSH signatures can be verified".to_string());
+        }
+
+        let signature =
+            SshSig::from_pem(signature).map_err(|e| format!("malformed SSH signature: {}", e))?;
+
+        let signer = self
+            .signers
+            .iter()
+            .find(|signer| signer.key.key_data() == signature.public_key())
+            .ok_or_else(|| "signing key is not an allowed signer".to_string())?;
+
+        signer
+            .key
+            .verify(NAMESPACE, payload, &signature)
+            .map_err(|e| format!("bad signature: {}", e))?;
+
+        Ok(signer.principals.clone())
+    }
+}
+
+/// Resolve the allowed signers file: `config.allowed_signers`, else git's
+/// `gpg.ssh.allowedSignersFile`.
+pub fn allowed_signers_path(repo: &gix::Repository, config: &Config) -> Option<PathBuf> {
+    config.allowed_signers.clone().or_else(|| {
+        repo.config_snapshot()
+            .trusted_path("gpg.ssh.allowedSignersFile")
+            .and_then(|path| path.ok())
+            .map(|path| path.into_owned())
+    })
+}
+
+fn is_key_type(token: &str) -> bool {
+    ["ssh-", "ecdsa-", "sk-ssh-", "sk-ecdsa-"]
+        .iter()
+        .any(|prefix| token.starts_with(prefix))
+}
+
+/// Whether an entry's comma-separated options permit signatures in the git namespace.
+fn options_allow_git(options: &str) -> bool {
+    let mut allowed = true;
+
+    for option in split_quoted(options, |c| c == ',') {
+        let (name, value) = option.split_once('=').unwrap_or((option.as_str(), ""));
+        match name.to_ascii_lowercase().as_str() {
+            "namespaces" => {
+                allowed &= value
+                    .trim_matches('"')
+                    .split(',')
+                    .any(|namespace| namespace == NAMESPACE);
+            }
+            "cert-authority" | "valid-after" | "valid-before" => {
+                tracing::warn!("Ignoring allowed signer with unsupported option '{}'", name);
+                return false;
+            }
+            _ => {}
+        }
+    }
+
+    allowed
+}
+
+/// Split on separators outside double quotes, dropping empty parts.
+fn split_quoted(text: &str, is_separator: impl Fn(char) -> bool) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+
+    for c in text.chars() {
+        if c == '"' {
+            quoted = !quoted;
+        }
+        if !quoted && is_separator(c) {
+            if !current.is_empty() {
+                parts.push(std::mem::take(&mut current));
+            }
+        } else {
+            current.push(c);
+        }
+    }
+    if !current.is_empty() {
+        parts.push(current);
+    }
+
+    parts
+}
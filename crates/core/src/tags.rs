@@ -1,9 +1,12 @@
 //! Tag parsing and management functionality.
 
-use crate::config::Config;
+use crate::config::{Config, TagRequirement, TagSelection};
 use crate::error::{Result, TagVerError};
+use crate::git::GixGraph;
+use crate::graph::CommitGraph;
+use crate::signing::{self, AllowedSigners};
 use crate::version::Version;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 /// A map from commit IDs to their version tags.
 pub type TagMap = HashMap<gix::ObjectId, Vec<VersionTag>>;
@@ -13,66 +16,427 @@ pub type TagMap = HashMap<gix::ObjectId, Vec<VersionTag>>;
 pub struct VersionTag {
     pub version: Version,
     pub tag_name: String,
+    /// The tagger time of an annotated tag, in seconds since the Unix epoch. Only read for
+    /// [`TagSelection::Newest`].
+    pub tagger_time: Option<i64>,
+}
+
+/// Why a tag is not used as a version tag.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SkipReason {
+    /// Matches one of the exclude patterns.
+    Excluded,
+    /// Matches neither the tag prefix nor any of the tag patterns.
+    NoMatch,
+    /// The version text is not valid SemVer.
+    InvalidVersion,
+    /// Points to an object of the given kind instead of a commit.
+    NotACommit(String),
+    /// The target object can't be read.
+    Unresolved,
+    /// A lightweight tag, while annotated tags are required.
+    Lightweight,
+    /// An annotated tag without a signature, while signed tags are required.
+    Unsigned,
+    /// The signature couldn't be verified, for the given reason.
+    Unverified(String),
+}
+
+impl std::fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SkipReason::Excluded => write!(f, "excluded by pattern"),
+            SkipReason::NoMatch => write!(f, "no matching prefix or pattern"),
+            SkipReason::InvalidVersion => write!(f, "not a valid SemVer version"),
+            SkipReason::NotACommit(kind) => write!(f, "points to a {}, not a commit", kind),
+            SkipReason::Unresolved => write!(f, "target can't be resolved"),
+            SkipReason::Lightweight => write!(f, "lightweight tag"),
+            SkipReason::Unsigned => write!(f, "not signed"),
+            SkipReason::Unverified(reason) => write!(f, "signature not verified: {}", reason),
+        }
+    }
+}
+
+/// A tag with the diagnostics reported by [`list_tags`].
+#[derive(Debug, Clone)]
+pub struct TagInfo {
+    pub tag_name: String,
+    /// The parsed version, when the name yields valid SemVer.
+    pub version: Option<Version>,
+    /// The object the tag points to, after peeling annotated tags.
+    pub target: Option<gix::ObjectId>,
+    pub annotated: bool,
+    /// Whether the target is HEAD (or the configured revision) or one of its ancestors.
+    pub reachable: bool,
+    /// Why the tag is ignored, if it is.
+    pub skipped: Option<SkipReason>,
 }
 
 /// Parse all tags in the repository that match the configured prefix.
 ///
+/// Tags pointing to trees or blobs are skipped with a warning, as are tags whose signature
+/// fails verification. Tags not meeting `config.tag_requirement` are skipped.
+///
 /// Returns:
 /// - TagMap: mapping from commit ObjectId to list of version tags
 /// - `Vec<String>`: list of invalid/unparseable tags (for warnings)
 pub fn parse_tags(repo: &gix::Repository, config: &Config) -> Result<(TagMap, Vec<String>)> {
     let mut tag_map: TagMap = HashMap::new();
     let mut invalid_tags: Vec<String> = Vec::new();
+    let filter = TagFilter::new(repo, config)?;
 
-    // Get all references
-    let refs = repo
-        .references()
-        .map_err(|e| TagVerError::Other(format!("Failed to get references: {}", e)))?;
-
-    // Filter for tags
-    let tag_refs = refs
-        .tags()
-        .map_err(|e| TagVerError::Other(format!("Failed to get tags: {}", e)))?;
-
-    for mut tag_ref in tag_refs.flatten() {
+    for mut tag_ref in tag_refs(repo)? {
         let tag_name = tag_ref.name().shorten().to_string();
 
-        // Apply prefix filter
-        let version_str = if config.tag_prefix.is_empty() {
-            tag_name.clone()
-        } else if tag_name.starts_with(&config.tag_prefix) {
-            tag_name[config.tag_prefix.len()..].to_string()
-        } else {
-            continue; // Skip tags that don't match prefix
+        // Apply prefix or pattern filter
+        let version_str = match version_text(&tag_name, config) {
+            Ok(text) => text.to_string(),
+            Err(reason) => {
+                log_skipped(&tag_name, &reason);
+                continue;
+            }
         };
 
         // Parse as semver
         match version_str.parse::<semver::Version>() {
             Ok(semver) => {
+                // Check the tag kind and signature
+                if let Err(reason) = filter.check(repo, &tag_ref) {
+                    log_skipped(&tag_name, &reason);
+                    continue;
+                }
+
+                // Read before peeling, which moves the ref to the target
+                let tagger_time = match config.tag_selection {
+                    TagSelection::Newest => {
+                        tag_object(repo, &tag_ref).and_then(|object| tagger_time(&object.data))
+                    }
+                    _ => None,
+                };
+
                 // Resolve the tag to its target commit
-                let target_id = match tag_ref.peel_to_id() {
-                    Ok(id) => id.detach(),
-                    Err(_) => continue, // Skip if we can't resolve
+                let target_id = match resolve_target(repo, &mut tag_ref) {
+                    Target::Commit(id) => id,
+                    Target::Other(_, kind) => {
+                        log_skipped(&tag_name, &SkipReason::NotACommit(kind));
+                        continue;
+                    }
+                    Target::Unresolved => {
+                        log_skipped(&tag_name, &SkipReason::Unresolved);
+                        continue;
+                    }
                 };
 
                 let version = Version::from_semver_full(&semver);
                 let version_tag = VersionTag {
                     version,
                     tag_name: tag_name.clone(),
+                    tagger_time,
                 };
 
                 tag_map.entry(target_id).or_default().push(version_tag);
             }
             Err(_) => {
+                log_skipped(&tag_name, &SkipReason::InvalidVersion);
                 invalid_tags.push(tag_name);
             }
         }
     }
 
-    // Sort tags at each commit by version (highest first)
+    tracing::Span::current().record("invalid", invalid_tags.len());
+    sort_tag_map(&mut tag_map, config.tag_selection);
+
+    Ok((tag_map, invalid_tags))
+}
+
+/// Sort the tags at each commit so the selected one comes first. Ties are broken by
+/// precedence and then by tag name, so the order never depends on ref iteration.
+pub(crate) fn sort_tag_map(tag_map: &mut TagMap, selection: TagSelection) {
     for tags in tag_map.values_mut() {
-        tags.sort_by(|a, b| b.version.cmp(&a.version));
+        tags.sort_by(|a, b| {
+            let preferred = match selection {
+                TagSelection::Precedence => std::cmp::Ordering::Equal,
+                TagSelection::PreferRtm => b.version.is_rtm().cmp(&a.version.is_rtm()),
+                TagSelection::Newest => b.tagger_time.cmp(&a.tagger_time),
+            };
+            preferred
+                .then_with(|| b.version.cmp(&a.version))
+                .then_with(|| a.tag_name.cmp(&b.tag_name))
+        });
     }
+}
 
-    Ok((tag_map, invalid_tags))
+/// Return the selected tag of a commit's sorted tags, logging the others at verbose level.
+pub(crate) fn select_tag(tags: &[VersionTag]) -> Option<&VersionTag> {
+    let (selected, others) = tags.split_first()?;
+    if !others.is_empty() {
+        let others: Vec<&str> = others.iter().map(|tag| tag.tag_name.as_str()).collect();
+        tracing::info!(
+            "Using tag '{}' over '{}' on the same commit",
+            selected.tag_name,
+            others.join("', '")
+        );
+    }
+    Some(selected)
+}
+
+/// The tagger time in the raw data of a tag object.
+pub(crate) fn tagger_time(data: &[u8]) -> Option<i64> {
+    let header = data
+        .split(|&b| b == b'\n')
+        .take_while(|line| !line.is_empty());
+    for line in header {
+        if let Some(tagger) = line.strip_prefix(b"tagger ") {
+            // `Name <email> <seconds> <offset>`
+            let tagger = std::str::from_utf8(tagger).ok()?;
+            return tagger.rsplit(' ').nth(1)?.parse().ok();
+        }
+    }
+    None
+}
+
+/// The version of a tag, when it matches the prefix or patterns and is valid SemVer.
+pub(crate) fn tag_version(
+    tag_name: &str,
+    config: &Config,
+) -> std::result::Result<Version, SkipReason> {
+    let semver = version_text(tag_name, config)?
+        .parse::<semver::Version>()
+        .map_err(|_| SkipReason::InvalidVersion)?;
+    Ok(Version::from_semver_full(&semver))
+}
+
+/// Log why a tag is ignored. Failed verification and tags on non-commits are warnings,
+/// since they point to a mistake; tags unrelated to versioning are only traced.
+pub(crate) fn log_skipped(tag_name: &str, reason: &SkipReason) {
+    match reason {
+        SkipReason::Unverified(_) | SkipReason::NotACommit(_) | SkipReason::Unresolved => {
+            tracing::warn!(tag = tag_name, reason = %reason, "Ignoring tag '{}': {}", tag_name, reason);
+        }
+        SkipReason::NoMatch => {
+            tracing::trace!(tag = tag_name, reason = %reason, "Ignoring tag '{}': {}", tag_name, reason);
+        }
+        _ => {
+            tracing::debug!(tag = tag_name, reason = %reason, "Ignoring tag '{}': {}", tag_name, reason);
+        }
+    }
+}
+
+/// List every tag in the repository, whether used as a version tag or not, with the
+/// reason it is skipped and whether it is reachable from HEAD (or `config.revision`).
+///
+/// Reachability follows all parents and replace refs, so tags on merged branches are
+/// reachable while tags on abandoned branches are not. Tags are returned in ref name order.
+pub fn list_tags(repo: &gix::Repository, config: &Config) -> Result<Vec<TagInfo>> {
+    let reachable = match crate::git::target_commit(repo, config)? {
+        Some(head) => GixGraph::new(repo, config)
+            .reachable(&[head], None)
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect(),
+        None => HashSet::new(),
+    };
+
+    let filter = TagFilter::new(repo, config)?;
+    let mut tags = Vec::new();
+
+    for mut tag_ref in tag_refs(repo)? {
+        let tag_name = tag_ref.name().shorten().to_string();
+        let annotated = tag_object(repo, &tag_ref).is_some();
+
+        let mut skipped = None;
+        let version = match version_text(&tag_name, config) {
+            Ok(text) => match text.parse::<semver::Version>() {
+                Ok(semver) => Some(Version::from_semver_full(&semver)),
+                Err(_) => {
+                    skipped = Some(SkipReason::InvalidVersion);
+                    None
+                }
+            },
+            Err(reason) => {
+                skipped = Some(reason);
+                None
+            }
+        };
+
+        if skipped.is_none() {
+            skipped = filter.check(repo, &tag_ref).err();
+        }
+
+        let target = match resolve_target(repo, &mut tag_ref) {
+            Target::Commit(id) => Some(id),
+            Target::Other(id, kind) => {
+                skipped.get_or_insert(SkipReason::NotACommit(kind));
+                Some(id)
+            }
+            Target::Unresolved => {
+                skipped.get_or_insert(SkipReason::Unresolved);
+                None
+            }
+        };
+
+        tags.push(TagInfo {
+            tag_name,
+            version,
+            target,
+            annotated,
+            reachable: target.is_some_and(|id| reachable.contains(&id)),
+            skipped,
+        });
+    }
+
+    Ok(tags)
+}
+
+fn tag_refs(repo: &gix::Repository) -> Result<Vec<gix::Reference<'_>>> {
+    // Get all references
+    let refs = repo
+        .references()
+        .map_err(|e| TagVerError::Other(format!("Failed to get references: {}", e)))?;
+
+    // Filter for tags
+    let tag_refs = refs
+        .tags()
+        .map_err(|e| TagVerError::Other(format!("Failed to get tags: {}", e)))?;
+
+    Ok(tag_refs.flatten().collect())
+}
+
+/// The object a tag points to, after peeling annotated tags.
+enum Target {
+    Commit(gix::ObjectId),
+    Other(gix::ObjectId, String),
+    Unresolved,
+}
+
+fn resolve_target(repo: &gix::Repository, tag_ref: &mut gix::Reference<'_>) -> Target {
+    let Ok(id) = tag_ref.peel_to_id() else {
+        return Target::Unresolved;
+    };
+    let id = id.detach();
+
+    match repo.find_header(id) {
+        Ok(header) if header.kind() == gix::object::Kind::Commit => Target::Commit(id),
+        Ok(header) => Target::Other(id, header.kind().to_string()),
+        Err(_) => Target::Unresolved,
+    }
+}
+
+/// The tag object of an annotated tag, or `None` for a lightweight tag.
+fn tag_object<'repo>(
+    repo: &'repo gix::Repository,
+    tag_ref: &gix::Reference<'_>,
+) -> Option<gix::Object<'repo>> {
+    let id = tag_ref.target().try_id()?.to_owned();
+    let object = repo.find_object(id).ok()?;
+    (object.kind == gix::object::Kind::Tag).then_some(object)
+}
+
+/// Enforces `config.tag_requirement`.
+pub(crate) struct TagFilter {
+    requirement: TagRequirement,
+    allowed_signers: Option<AllowedSigners>,
+}
+
+impl TagFilter {
+    fn new(repo: &gix::Repository, config: &Config) -> Result<Self> {
+        Self::with_allowed_signers(config, signing::allowed_signers_path(repo, config))
+    }
+
+    /// Create a filter verifying signatures with the allowed signers file at `path`, which
+    /// is required when tags are verified.
+    pub(crate) fn with_allowed_signers(
+        config: &Config,
+        path: Option<std::path::PathBuf>,
+    ) -> Result<Self> {
+        let allowed_signers = if config.tag_requirement == TagRequirement::Verified {
+            let path = path.ok_or_else(|| {
+                TagVerError::InvalidAllowedSigners(
+                    "no file configured; set allowed_signers or gpg.ssh.allowedSignersFile"
+                        .to_string(),
+                )
+            })?;
+            Some(AllowedSigners::read(&path)?)
+        } else {
+            None
+        };
+
+        Ok(Self {
+            requirement: config.tag_requirement.clone(),
+            allowed_signers,
+        })
+    }
+
+    /// Whether the filter needs the tag object to check a tag.
+    pub(crate) fn needs_tag_object(&self) -> bool {
+        self.requirement != TagRequirement::Any
+    }
+
+    fn check(
+        &self,
+        repo: &gix::Repository,
+        tag_ref: &gix::Reference<'_>,
+    ) -> std::result::Result<(), SkipReason> {
+        if !self.needs_tag_object() {
+            return Ok(());
+        }
+
+        let object = tag_object(repo, tag_ref);
+        self.check_tag_object(object.as_ref().map(|object| object.data.as_slice()))
+    }
+
+    /// Check a tag given the raw data of its tag object, or `None` for a lightweight tag.
+    pub(crate) fn check_tag_object(
+        &self,
+        data: Option<&[u8]>,
+    ) -> std::result::Result<(), SkipReason> {
+        if self.requirement == TagRequirement::Any {
+            return Ok(());
+        }
+
+        let data = data.ok_or(SkipReason::Lightweight)?;
+        if self.requirement == TagRequirement::Annotated {
+            return Ok(());
+        }
+
+        let (payload, signature) = signing::split_signature(data).ok_or(SkipReason::Unsigned)?;
+
+        match &self.allowed_signers {
+            Some(allowed_signers) => allowed_signers
+                .verify(payload, signature)
+                .map(|principals| {
+                    tracing::debug!("Tag signed by {}", principals);
+                })
+                .map_err(SkipReason::Unverified),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Extract the version text from a tag name according to the configured patterns.
+///
+/// Exclusions are checked first. Patterns are tried in order and the first match wins;
+/// without patterns, the tag prefix is stripped.
+pub(crate) fn version_text<'a>(
+    tag_name: &'a str,
+    config: &Config,
+) -> std::result::Result<&'a str, SkipReason> {
+    if config
+        .exclude_tag_patterns
+        .iter()
+        .any(|pattern| pattern.matches(tag_name))
+    {
+        return Err(SkipReason::Excluded);
+    }
+
+    let text = if config.tag_patterns.is_empty() {
+        tag_name.strip_prefix(config.tag_prefix.as_str())
+    } else {
+        config
+            .tag_patterns
+            .iter()
+            .find_map(|pattern| pattern.extract_version(tag_name))
+    };
+
+    text.ok_or(SkipReason::NoMatch)
 }
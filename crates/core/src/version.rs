@@ -1,6 +1,7 @@
 //! Version representation and calculation.
 
 use crate::config::{MajorMinor, VersionPart};
+use crate::error::TagVerError;
 use serde::Serialize;
 
 /// Semantic version representation used by TagVer.
@@ -16,16 +17,16 @@ use serde::Serialize;
 /// ```
 #[derive(Debug, Clone, Serialize)]
 pub struct Version {
-    pub major: u32,
-    pub minor: u32,
-    pub patch: u32,
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
     pub prerelease: Vec<String>,
     pub build_metadata: Option<String>,
 }
 
 impl Version {
     /// Create a new version.
-    pub fn new(major: u32, minor: u32, patch: u32) -> Self {
+    pub fn new(major: u64, minor: u64, patch: u64) -> Self {
         Self {
             major,
             minor,
@@ -38,9 +39,9 @@ impl Version {
     /// Create a version from semver.
     pub fn from_semver(semver: &semver::Version) -> Self {
         Self {
-            major: semver.major as u32,
-            minor: semver.minor as u32,
-            patch: semver.patch as u32,
+            major: semver.major,
+            minor: semver.minor,
+            patch: semver.patch,
             prerelease: vec![],
             build_metadata: None,
         }
@@ -63,17 +64,27 @@ impl Version {
         };
 
         Self {
-            major: semver.major as u32,
-            minor: semver.minor as u32,
-            patch: semver.patch as u32,
+            major: semver.major,
+            minor: semver.minor,
+            patch: semver.patch,
             prerelease,
             build_metadata,
         }
     }
 
+    /// Parse a SemVer version, preserving prerelease and build metadata, unlike `FromStr`.
+    ///
+    /// # Errors
+    /// [`TagVerError::InvalidSemver`] if `s` isn't a valid SemVer version.
+    pub fn parse(s: &str) -> Result<Self, TagVerError> {
+        let semver = semver::Version::parse(s)
+            .map_err(|e| TagVerError::InvalidSemver(format!("'{}': {}", s, e)))?;
+        Ok(Version::from_semver_full(&semver))
+    }
+
     /// Convert to semver.
     pub fn to_semver(&self) -> semver::Version {
-        semver::Version::new(self.major as u64, self.minor as u64, self.patch as u64)
+        semver::Version::new(self.major, self.minor, self.patch)
     }
 
     /// Check if this version is a pre-release (has prerelease identifiers).
@@ -92,10 +103,16 @@ impl Version {
     /// - Major: 2.0.0 -> 3.0.0
     /// - Minor: 2.3.0 -> 2.4.0
     /// - Patch: 2.3.4 -> 2.3.5
-    pub fn increment(&self, part: &VersionPart) -> Self {
-        match part {
+    ///
+    /// # Errors
+    /// [`TagVerError::VersionOverflow`] if the incremented component exceeds `u64::MAX`.
+    pub fn increment(&self, part: &VersionPart) -> Result<Self, TagVerError> {
+        let overflow =
+            || TagVerError::VersionOverflow(format!("Cannot increment {:?} of {}", part, self));
+
+        Ok(match part {
             VersionPart::Major => Self {
-                major: self.major + 1,
+                major: self.major.checked_add(1).ok_or_else(overflow)?,
                 minor: 0,
                 patch: 0,
                 prerelease: Vec::new(),
@@ -103,7 +120,7 @@ impl Version {
             },
             VersionPart::Minor => Self {
                 major: self.major,
-                minor: self.minor + 1,
+                minor: self.minor.checked_add(1).ok_or_else(overflow)?,
                 patch: 0,
                 prerelease: Vec::new(),
                 build_metadata: None,
@@ -111,13 +128,115 @@ impl Version {
             VersionPart::Patch => Self {
                 major: self.major,
                 minor: self.minor,
-                patch: self.patch + 1,
+                patch: self.patch.checked_add(1).ok_or_else(overflow)?,
                 prerelease: Vec::new(),
                 build_metadata: None,
             },
+        })
+    }
+
+    /// The next release when bumping `part`. A pre-release is followed by its own release
+    /// when that is already a `part` bump, like npm and Cargo do:
+    /// - Major: 1.2.3 -> 2.0.0, 2.0.0-rc.1 -> 2.0.0, 2.1.0-rc.1 -> 3.0.0
+    /// - Minor: 1.2.3 -> 1.3.0, 1.3.0-rc.1 -> 1.3.0, 1.3.1-rc.1 -> 1.4.0
+    /// - Patch: 1.2.3 -> 1.2.4, 1.2.4-rc.1 -> 1.2.4
+    ///
+    /// # Errors
+    /// [`TagVerError::VersionOverflow`] if the incremented component exceeds `u64::MAX`.
+    pub fn next_release(&self, part: &VersionPart) -> Result<Self, TagVerError> {
+        let is_own_release = self.is_prerelease()
+            && match part {
+                VersionPart::Major => self.minor == 0 && self.patch == 0,
+                VersionPart::Minor => self.patch == 0,
+                VersionPart::Patch => true,
+            };
+
+        if is_own_release {
+            Ok(Version::new(self.major, self.minor, self.patch))
+        } else {
+            self.increment(part)
         }
     }
 
+    /// Bump the pre-release by incrementing its last numeric identifier (`rc.1` -> `rc.2`,
+    /// `alpha.0.5` -> `alpha.0.6`), or appending `1` when it has none (`rc` -> `rc.1`).
+    /// A release starts the pre-releases of the next version: `part` is incremented and
+    /// `default_prerelease` appended (`1.2.3` -> `1.2.4-alpha.0`).
+    ///
+    /// # Errors
+    /// [`TagVerError::VersionOverflow`] if the identifier or component overflows.
+    pub fn increment_prerelease(
+        &self,
+        part: &VersionPart,
+        default_prerelease: &[String],
+    ) -> Result<Self, TagVerError> {
+        if self.is_rtm() {
+            return Ok(self
+                .increment(part)?
+                .with_appended_prerelease(default_prerelease));
+        }
+
+        let mut next = Version {
+            build_metadata: None,
+            ..self.clone()
+        };
+        let is_numeric = |id: &String| !id.is_empty() && id.bytes().all(|b| b.is_ascii_digit());
+        match next.prerelease.iter().rposition(is_numeric) {
+            Some(index) => {
+                let number = next.prerelease[index]
+                    .parse::<u64>()
+                    .ok()
+                    .and_then(|number| number.checked_add(1))
+                    .ok_or_else(|| {
+                        TagVerError::VersionOverflow(format!(
+                            "Cannot increment the pre-release of {}",
+                            self
+                        ))
+                    })?;
+                next.prerelease[index] = number.to_string();
+            }
+            None => next.prerelease.push("1".to_string()),
+        }
+        Ok(next)
+    }
+
+    /// Promote the pre-release to the next of `phases`, matched against its first identifier
+    /// regardless of case: with `alpha`, `beta` and `rc`, `1.0.0-alpha.3` -> `1.0.0-beta.1`
+    /// and `1.0.0-beta.2` -> `1.0.0-rc.1`. The last phase is promoted to the release
+    /// (`1.0.0-rc.2` -> `1.0.0`).
+    ///
+    /// # Errors
+    /// [`TagVerError::InvalidPrerelease`] if the version is a release or its phase isn't one
+    /// of `phases`.
+    pub fn promote(&self, phases: &[String]) -> Result<Self, TagVerError> {
+        let phase = self.prerelease.first().ok_or_else(|| {
+            TagVerError::InvalidPrerelease(format!("{} is not a pre-release", self))
+        })?;
+        let index = phases
+            .iter()
+            .position(|p| p.eq_ignore_ascii_case(phase))
+            .ok_or_else(|| {
+                TagVerError::InvalidPrerelease(format!(
+                    "'{}' of {} is not one of the phases {}",
+                    phase,
+                    self,
+                    phases.join(", ")
+                ))
+            })?;
+
+        Ok(match phases.get(index + 1) {
+            Some(next) => Version::new(self.major, self.minor, self.patch)
+                .with_appended_prerelease(&[next.clone(), "1".to_string()]),
+            None => self.finalize(),
+        })
+    }
+
+    /// The release of this version, without pre-release or build metadata
+    /// (`1.0.0-rc.2+abc` -> `1.0.0`).
+    pub fn finalize(&self) -> Self {
+        Version::new(self.major, self.minor, self.patch)
+    }
+
     /// Check if this version satisfies the minimum major.minor constraint.
     pub fn satisfies(&self, min_major_minor: &MajorMinor, _default_prerelease: &[String]) -> Self {
         if self.major > min_major_minor.major
@@ -134,27 +253,46 @@ impl Version {
     /// Used when: We're past an RTM tag by `height` commits.
     /// Result: {incremented_version}-{default_prerelease}.{height}
     /// Example: 1.2.3 + Patch + height=5 + "alpha.0" -> 1.2.4-alpha.0.5
+    ///
+    /// # Errors
+    /// [`TagVerError::VersionOverflow`] if the auto-increment overflows.
     pub fn with_rtm_height(
         &self,
         height: u32,
         auto_increment: &VersionPart,
         default_prerelease: &[String],
-    ) -> Self {
+    ) -> Result<Self, TagVerError> {
         if height == 0 {
-            return self.clone();
+            return Ok(self.clone());
         }
 
-        let incremented = self.increment(auto_increment);
+        self.with_rtm_prerelease(&[height.to_string()], auto_increment, default_prerelease)
+    }
+
+    /// Increment an RTM version and follow the default prerelease with extra identifiers.
+    ///
+    /// Result: {incremented_version}-{default_prerelease}.{identifiers}
+    /// Example: 1.2.3 + Patch + "alpha.0" + ["dev", "5"] -> 1.2.4-alpha.0.dev.5
+    ///
+    /// # Errors
+    /// [`TagVerError::VersionOverflow`] if the auto-increment overflows.
+    pub fn with_rtm_prerelease(
+        &self,
+        identifiers: &[String],
+        auto_increment: &VersionPart,
+        default_prerelease: &[String],
+    ) -> Result<Self, TagVerError> {
+        let incremented = self.increment(auto_increment)?;
         let mut prerelease = default_prerelease.to_vec();
-        prerelease.push(height.to_string());
+        prerelease.extend_from_slice(identifiers);
 
-        Self {
+        Ok(Self {
             major: incremented.major,
             minor: incremented.minor,
             patch: incremented.patch,
             prerelease,
             build_metadata: None,
-        }
+        })
     }
 
     /// Append height to existing prerelease identifiers.
@@ -167,8 +305,15 @@ impl Version {
             return self.clone();
         }
 
+        self.with_appended_prerelease(&[height.to_string()])
+    }
+
+    /// Append identifiers to the existing prerelease identifiers.
+    ///
+    /// Example: 1.0.0-beta.1 + ["dev", "3"] -> 1.0.0-beta.1.dev.3
+    pub fn with_appended_prerelease(&self, identifiers: &[String]) -> Self {
         let mut prerelease = self.prerelease.clone();
-        prerelease.push(height.to_string());
+        prerelease.extend_from_slice(identifiers);
 
         Self {
             major: self.major,
@@ -229,18 +374,26 @@ impl Version {
     }
 
     /// Create version with height.
+    ///
+    /// # Errors
+    /// [`TagVerError::VersionOverflow`] if adding the height overflows the patch.
     pub fn with_height(
         &self,
         height: u32,
         _auto_increment: &VersionPart,
         default_prerelease: &[String],
-    ) -> Self {
+    ) -> Result<Self, TagVerError> {
         let mut version = self.clone();
-        version.patch += height;
+        version.patch = version
+            .patch
+            .checked_add(u64::from(height))
+            .ok_or_else(|| {
+                TagVerError::VersionOverflow(format!("Cannot add height {} to {}", height, self))
+            })?;
         if height > 0 {
             version.prerelease = default_prerelease.to_vec();
         }
-        version
+        Ok(version)
     }
 
     /// Create version with build metadata.
@@ -250,6 +403,83 @@ impl Version {
         version
     }
 
+    /// Check that every prerelease and build metadata identifier is valid SemVer, so the
+    /// version round-trips through the `semver` crate.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use tagver::{TagVerError, Version};
+    ///
+    /// let version = Version::new(1, 0, 0).with_build_metadata("feature/x y");
+    /// assert!(matches!(version.validate(), Err(TagVerError::InvalidBuildMetadata(_))));
+    /// assert_eq!(version.sanitized().to_string(), "1.0.0+feature-x-y");
+    /// ```
+    ///
+    /// # Errors
+    /// - [`TagVerError::InvalidPrerelease`] for an invalid prerelease identifier.
+    /// - [`TagVerError::InvalidBuildMetadata`] for an invalid build metadata identifier.
+    pub fn validate(&self) -> Result<(), TagVerError> {
+        for identifier in &self.prerelease {
+            if let Some(reason) = invalid_identifier_reason(identifier, true) {
+                return Err(TagVerError::InvalidPrerelease(format!(
+                    "'{}' in '{}' {}",
+                    identifier, self, reason
+                )));
+            }
+        }
+
+        if let Some(ref build) = self.build_metadata {
+            for identifier in build.split('.') {
+                if let Some(reason) = invalid_identifier_reason(identifier, false) {
+                    return Err(TagVerError::InvalidBuildMetadata(format!(
+                        "'{}' in '{}' {}",
+                        identifier, self, reason
+                    )));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Rewrite invalid identifiers into valid SemVer.
+    ///
+    /// Invalid characters become `-`, empty identifiers are dropped and leading zeros are
+    /// stripped from numeric prerelease identifiers.
+    pub fn sanitized(&self) -> Self {
+        let prerelease = self
+            .prerelease
+            .iter()
+            .filter(|identifier| !identifier.is_empty())
+            .map(|identifier| {
+                let identifier = sanitize_identifier(identifier);
+                if identifier.chars().all(|c| c.is_ascii_digit()) {
+                    let trimmed = identifier.trim_start_matches('0');
+                    if trimmed.is_empty() { "0" } else { trimmed }.to_string()
+                } else {
+                    identifier
+                }
+            })
+            .collect();
+
+        let build_metadata = self.build_metadata.as_ref().and_then(|build| {
+            let identifiers: Vec<String> = build
+                .split('.')
+                .filter(|identifier| !identifier.is_empty())
+                .map(sanitize_identifier)
+                .collect();
+            (!identifiers.is_empty()).then(|| identifiers.join("."))
+        });
+
+        Self {
+            major: self.major,
+            minor: self.minor,
+            patch: self.patch,
+            prerelease,
+            build_metadata,
+        }
+    }
+
     /// Create default version.
     pub fn default(default_prerelease: &[String]) -> Self {
         Self {
@@ -262,6 +492,40 @@ impl Version {
     }
 }
 
+/// Explain why `identifier` is not a valid SemVer identifier, if it isn't.
+fn invalid_identifier_reason(identifier: &str, is_prerelease: bool) -> Option<&'static str> {
+    if identifier.is_empty() {
+        Some("is empty")
+    } else if !identifier
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-')
+    {
+        Some("may only contain ASCII alphanumerics and hyphens")
+    } else if is_prerelease
+        && identifier.len() > 1
+        && identifier.starts_with('0')
+        && identifier.chars().all(|c| c.is_ascii_digit())
+    {
+        Some("is numeric with a leading zero")
+    } else {
+        None
+    }
+}
+
+/// Replace every character that is not allowed in a SemVer identifier with `-`.
+pub(crate) fn sanitize_identifier(identifier: &str) -> String {
+    identifier
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect()
+}
+
 impl std::fmt::Display for Version {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}.{}.{}", self.major, self.minor, self.patch)?;
@@ -305,19 +569,57 @@ impl PartialOrd for Version {
     }
 }
 
+/// SemVer precedence: build metadata is ignored, and a pre-release is lower than the
+/// release with the same major, minor and patch.
 impl Ord for Version {
     fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        // Simple version comparison
+        use std::cmp::Ordering;
+
         self.major
             .cmp(&other.major)
             .then(self.minor.cmp(&other.minor))
             .then(self.patch.cmp(&other.patch))
+            .then_with(
+                || match (self.prerelease.is_empty(), other.prerelease.is_empty()) {
+                    (true, true) => Ordering::Equal,
+                    (true, false) => Ordering::Greater,
+                    (false, true) => Ordering::Less,
+                    (false, false) => {
+                        for (a, b) in self.prerelease.iter().zip(&other.prerelease) {
+                            let ordering = compare_identifiers(a, b);
+                            if ordering != Ordering::Equal {
+                                return ordering;
+                            }
+                        }
+                        self.prerelease.len().cmp(&other.prerelease.len())
+                    }
+                },
+            )
+    }
+}
+
+/// Compare pre-release identifiers: numeric ones numerically and lower than alphanumeric
+/// ones, which compare in ASCII order.
+fn compare_identifiers(a: &str, b: &str) -> std::cmp::Ordering {
+    let is_numeric = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+
+    match (is_numeric(a), is_numeric(b)) {
+        // Without leading zeros, a longer number is larger; this also handles numbers
+        // beyond u64
+        (true, true) => a
+            .trim_start_matches('0')
+            .len()
+            .cmp(&b.trim_start_matches('0').len())
+            .then_with(|| a.trim_start_matches('0').cmp(b.trim_start_matches('0'))),
+        (true, false) => std::cmp::Ordering::Less,
+        (false, true) => std::cmp::Ordering::Greater,
+        (false, false) => a.cmp(b),
     }
 }
 
 impl MajorMinor {
     /// Create a new major.minor constraint.
-    pub fn new(major: u32, minor: u32) -> Self {
+    pub fn new(major: u64, minor: u64) -> Self {
         Self { major, minor }
     }
 }
@@ -331,4 +633,121 @@ mod tests {
         let version = Version::new(1, 2, 3);
         assert_eq!(version.to_string(), "1.2.3");
     }
+
+    #[test]
+    fn test_precedence() {
+        let parse = |s: &str| Version::from_semver_full(&s.parse().unwrap());
+
+        // The order from the SemVer specification
+        let versions = [
+            "1.0.0-alpha",
+            "1.0.0-alpha.1",
+            "1.0.0-alpha.beta",
+            "1.0.0-beta",
+            "1.0.0-beta.2",
+            "1.0.0-beta.11",
+            "1.0.0-rc.1",
+            "1.0.0",
+            "1.0.1-alpha.0.1",
+            "1.1.0-rc.1",
+            "1.1.0",
+        ];
+        for pair in versions.windows(2) {
+            assert!(parse(pair[0]) < parse(pair[1]), "{} < {}", pair[0], pair[1]);
+        }
+
+        assert_eq!(
+            parse("1.0.0+build.5").cmp(&parse("1.0.0")),
+            std::cmp::Ordering::Equal
+        );
+    }
+
+    #[test]
+    fn test_next_release() {
+        let parse = |s: &str| Version::from_semver_full(&s.parse().unwrap());
+        let cases = [
+            ("1.2.3", VersionPart::Major, "2.0.0"),
+            ("2.0.0-rc.1", VersionPart::Major, "2.0.0"),
+            ("2.1.0-rc.1", VersionPart::Major, "3.0.0"),
+            ("1.2.3", VersionPart::Minor, "1.3.0"),
+            ("1.3.0-rc.1", VersionPart::Minor, "1.3.0"),
+            ("1.3.1-rc.1", VersionPart::Minor, "1.4.0"),
+            ("1.2.3", VersionPart::Patch, "1.2.4"),
+            ("1.2.4-rc.1", VersionPart::Patch, "1.2.4"),
+        ];
+        for (version, part, expected) in cases {
+            let next = parse(version).next_release(&part).unwrap();
+            assert_eq!(next.to_string(), expected, "{} {:?}", version, part);
+        }
+    }
+
+    #[test]
+    fn test_increment_prerelease() {
+        let parse = |s: &str| Version::from_semver_full(&s.parse().unwrap());
+        let default = vec!["alpha".to_string(), "0".to_string()];
+        let cases = [
+            ("1.2.4-rc.1", "1.2.4-rc.2"),
+            ("1.2.4-rc.9+build", "1.2.4-rc.10"),
+            ("1.0.0-alpha.0.5", "1.0.0-alpha.0.6"),
+            ("1.0.0-rc.1.hotfix", "1.0.0-rc.2.hotfix"),
+            ("1.0.0-rc", "1.0.0-rc.1"),
+            ("1.2.3", "1.2.4-alpha.0"),
+        ];
+        for (version, expected) in cases {
+            let next = parse(version)
+                .increment_prerelease(&VersionPart::Patch, &default)
+                .unwrap();
+            assert_eq!(next.to_string(), expected, "{}", version);
+        }
+
+        let overflow = parse(&format!("1.0.0-rc.{}", u64::MAX));
+        assert!(matches!(
+            overflow.increment_prerelease(&VersionPart::Patch, &default),
+            Err(TagVerError::VersionOverflow(_))
+        ));
+    }
+
+    #[test]
+    fn test_promote_and_finalize() {
+        let parse = |s: &str| Version::from_semver_full(&s.parse().unwrap());
+        let phases: Vec<String> = ["alpha", "beta", "rc"].map(String::from).to_vec();
+        let cases = [
+            ("1.0.0-alpha.3", "1.0.0-beta.1"),
+            ("1.0.0-Beta.2.hotfix", "1.0.0-rc.1"),
+            ("1.0.0-rc.2+build", "1.0.0"),
+        ];
+        for (version, expected) in cases {
+            let next = parse(version).promote(&phases).unwrap();
+            assert_eq!(next.to_string(), expected, "{}", version);
+        }
+
+        for version in ["1.0.0", "1.0.0-preview.1"] {
+            assert!(matches!(
+                parse(version).promote(&phases),
+                Err(TagVerError::InvalidPrerelease(_))
+            ));
+        }
+        let custom: Vec<String> = ["preview", "rc"].map(String::from).to_vec();
+        assert_eq!(
+            parse("1.0.0-preview.1")
+                .promote(&custom)
+                .unwrap()
+                .to_string(),
+            "1.0.0-rc.1"
+        );
+
+        assert_eq!(parse("1.0.0-rc.2+abc").finalize().to_string(), "1.0.0");
+        assert_eq!(parse("1.2.3").finalize().to_string(), "1.2.3");
+    }
+
+    #[test]
+    fn test_increment_overflow() {
+        let version = Version::new(u64::MAX, u64::MAX, u64::MAX);
+        for part in [VersionPart::Major, VersionPart::Minor, VersionPart::Patch] {
+            assert!(matches!(
+                version.increment(&part),
+                Err(TagVerError::VersionOverflow(_))
+            ));
+        }
+    }
 }
@@ -0,0 +1,263 @@
+//! Backend tests - the in-memory graph, and the git CLI backend against gix
+
+use std::path::Path;
+
+use regex::Regex;
+use tagver::config::{Backend, HeightMetric, HeightSource, TagRequirement};
+use tagver::git::calculate_version_with;
+use tagver::graph::{CommitInfo, MemoryGraph};
+use tagver::{calculate_version, calculate_version_with_fallback, Config, TagVerError};
+use tempfile::TempDir;
+use test_case::test_case;
+
+mod common;
+
+fn commit(path: &Path, message: &str) {
+    common::git::run_git_command(&["commit", "--allow-empty", "-m", message], path)
+        .expect("Failed to create commit");
+}
+
+/// The shape of `height_metric.rs`: `1.0.0` on the root, three commits on `feature` merged
+/// into `main` after one more commit there.
+fn memory_graph_with_merged_branch() -> MemoryGraph {
+    let mut graph = MemoryGraph::new();
+    graph.commit("root", &[]);
+    graph.tag("1.0.0", "root");
+    graph.commit("feature 1", &["root"]);
+    graph.commit("feature 2", &["feature 1"]);
+    graph.commit("feature 3", &["feature 2"]);
+    graph.commit("main 1", &["root"]);
+    graph.commit("merge", &["main 1", "feature 3"]);
+    graph
+}
+
+#[test_case(HeightMetric::FirstParent, "1.0.1-alpha.0.2")]
+#[test_case(HeightMetric::MinVer, "1.0.1-alpha.0.2")]
+#[test_case(HeightMetric::Ancestry, "1.0.1-alpha.0.5")]
+#[test_case(HeightMetric::NoMerges, "1.0.1-alpha.0.4")]
+fn test_memory_graph_height_metric(metric: HeightMetric, expected: &str) {
+    let graph = memory_graph_with_merged_branch();
+    let config = Config {
+        height_metric: metric,
+        ..Default::default()
+    };
+
+    let (version, _, is_from_tag) =
+        calculate_version_with(&graph, &graph, &config).expect("Failed to calculate");
+    assert_eq!(version.to_string(), expected);
+    assert!(!is_from_tag);
+}
+
+#[test]
+fn test_memory_graph_revision_and_commit_count() {
+    let graph = memory_graph_with_merged_branch();
+
+    let config = Config {
+        revision: Some("feature 2".to_string()),
+        ..Default::default()
+    };
+    let (version, height, _) =
+        calculate_version_with(&graph, &graph, &config).expect("Failed to calculate");
+    assert_eq!(version.to_string(), "1.0.1-alpha.0.2");
+    assert_eq!(height, 2);
+
+    let config = Config {
+        revision: Some("1.0.0".to_string()),
+        ..Default::default()
+    };
+    let (version, _, is_from_tag) =
+        calculate_version_with(&graph, &graph, &config).expect("Failed to calculate");
+    assert_eq!(version.to_string(), "1.0.0");
+    assert!(is_from_tag);
+
+    let config = Config {
+        revision: Some("unknown".to_string()),
+        ..Default::default()
+    };
+    let err = calculate_version_with(&graph, &graph, &config).unwrap_err();
+    assert!(matches!(err, TagVerError::InvalidRevision(_)));
+
+    let mut config = Config::default();
+    config.height_format.source = HeightSource::CommitCount;
+    let (version, _, _) =
+        calculate_version_with(&graph, &graph, &config).expect("Failed to calculate");
+    assert_eq!(version.to_string(), "1.0.1-alpha.0.6");
+}
+
+#[test]
+fn test_memory_graph_exclusions_and_metadata() {
+    let mut graph = MemoryGraph::new();
+    graph.commit("root", &[]);
+    graph.tag("1.0.0", "root");
+    graph.commit("feat: something useful", &["root"]);
+    graph.commit_with(
+        "bump",
+        &["feat: something useful"],
+        CommitInfo {
+            message: "chore(deps): bump serde".to_string(),
+            author_email: "49699333+dependabot[bot]@users.noreply.github.com".to_string(),
+            time: 1_700_000_000,
+        },
+    );
+
+    let config = Config {
+        height_exclude_messages: vec![Regex::new(r"^chore\(deps\)").unwrap()],
+        build_metadata: Some("{commit_date}.{branch}".to_string()),
+        ..Default::default()
+    };
+    let (version, height, _) =
+        calculate_version_with(&graph, &graph, &config).expect("Failed to calculate");
+    // The graph has no branches, so only the date is filled in
+    assert_eq!(version.to_string(), "1.0.1-alpha.0.1+20231114");
+    assert_eq!(height, 1);
+}
+
+#[test]
+fn test_memory_graph_without_commits_or_usable_tags() {
+    let graph = MemoryGraph::new();
+    let (version, height, _) =
+        calculate_version_with(&graph, &graph, &Config::default()).expect("Failed to calculate");
+    assert_eq!(version.to_string(), "0.0.0-alpha.0");
+    assert_eq!(height, 0);
+
+    // Memory tags are lightweight
+    let mut graph = MemoryGraph::new();
+    graph.commit("root", &[]);
+    graph.tag("1.0.0", "root");
+    let config = Config {
+        tag_requirement: TagRequirement::Annotated,
+        ..Default::default()
+    };
+    let (version, _, _) =
+        calculate_version_with(&graph, &graph, &config).expect("Failed to calculate");
+    assert_eq!(version.to_string(), "0.0.0-alpha.0");
+}
+
+/// A merged branch with lightweight, annotated, nested, prefixed and non-commit tags.
+async fn repo_with_tags() -> TempDir {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let path = temp_dir.path();
+
+    common::git::ensure_empty_repository_and_commit(path)
+        .await
+        .expect("Failed to create repo");
+    common::git::tag(path, "1.0.0")
+        .await
+        .expect("Failed to create tag");
+    common::git::run_git_command(&["checkout", "-b", "feature"], path)
+        .expect("Failed to create branch");
+    commit(path, "feature 1");
+    common::git::annotated_tag(path, "1.1.0-beta.1", "Beta")
+        .await
+        .expect("Failed to create tag");
+    commit(path, "chore(deps): bump serde");
+    commit(path, "feature 3");
+    common::git::annotated_tag(path, "1.2.0-rc.0", "Release candidate")
+        .await
+        .expect("Failed to create tag");
+    // An annotated tag of the annotated tag, peeled to the same commit
+    common::git::run_git_command(
+        &["tag", "-a", "-m", "Nested", "1.2.0-rc.1", "1.2.0-rc.0"],
+        path,
+    )
+    .expect("Failed to create tag");
+    common::git::checkout(path, "main")
+        .await
+        .expect("Failed to checkout");
+    commit(path, "main 1");
+    common::git::tag(path, "v2.0.0")
+        .await
+        .expect("Failed to create tag");
+    common::git::run_git_command(&["tag", "3.0.0", "HEAD^{tree}"], path)
+        .expect("Failed to create tag");
+    common::git::run_git_command(
+        &["merge", "--no-ff", "-m", "Merge feature", "feature"],
+        path,
+    )
+    .expect("Failed to merge");
+
+    temp_dir
+}
+
+fn configs() -> Vec<Config> {
+    let metrics = [
+        HeightMetric::FirstParent,
+        HeightMetric::MinVer,
+        HeightMetric::Ancestry,
+        HeightMetric::NoMerges,
+    ];
+    let mut configs: Vec<Config> = metrics
+        .into_iter()
+        .map(|metric| Config {
+            height_metric: metric,
+            ..Default::default()
+        })
+        .collect();
+
+    configs.push(Config {
+        tag_prefix: "v".to_string(),
+        ..Default::default()
+    });
+    configs.push(Config {
+        tag_requirement: TagRequirement::Annotated,
+        height_metric: HeightMetric::Ancestry,
+        height_exclude_messages: vec![Regex::new(r"^chore").unwrap()],
+        ..Default::default()
+    });
+    configs.push(Config {
+        revision: Some("feature".to_string()),
+        build_metadata: Some("{sha7}".to_string()),
+        ..Default::default()
+    });
+    let mut config = Config::default();
+    config.height_format.source = HeightSource::CommitCount;
+    configs.push(config);
+
+    configs
+}
+
+#[tokio::test]
+async fn test_git_backend_matches_gix() {
+    let temp_dir = repo_with_tags().await;
+    let path = temp_dir.path();
+
+    for config in configs() {
+        let gix = calculate_version(path, &config).expect("Failed to calculate with gix");
+        let git = Config {
+            backend: Backend::Git,
+            ..config.clone()
+        };
+        let git = calculate_version(path, &git).expect("Failed to calculate with git");
+
+        assert_eq!(
+            git.version.to_string(),
+            gix.version.to_string(),
+            "{:?}",
+            config
+        );
+        assert_eq!(git.height, gix.height, "{:?}", config);
+        assert_eq!(git.is_from_tag, gix.is_from_tag, "{:?}", config);
+    }
+}
+
+#[tokio::test]
+async fn test_git_backend_without_repository() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let config = Config {
+        backend: Backend::Git,
+        ..Default::default()
+    };
+
+    let err = calculate_version(temp_dir.path(), &config).unwrap_err();
+    assert!(matches!(err, TagVerError::GitRepoNotFound(_)));
+
+    let result =
+        calculate_version_with_fallback(temp_dir.path(), &config).expect("Failed to calculate");
+    assert_eq!(result.to_string(), "0.0.0-alpha.0");
+
+    common::git::ensure_empty_repository(temp_dir.path())
+        .await
+        .expect("Failed to create repo");
+    let result = calculate_version(temp_dir.path(), &config).expect("Failed to calculate");
+    assert_eq!(result.to_string(), "0.0.0-alpha.0");
+}
@@ -153,3 +153,75 @@ async fn test_previous_tag(tag_name: &str, build_metadata: &str, expected_versio
     // Verify the version
     assert_eq!(result.to_string(), expected_version);
 }
+
+// Template scenarios
+#[tokio::test]
+async fn test_template_placeholders() {
+    use tagver::{calculate_version, Config};
+
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let path = temp_dir.path();
+
+    common::git::ensure_empty_repository_and_commit(path)
+        .await
+        .expect("Failed to create repo");
+    common::git::tag(path, "1.2.3")
+        .await
+        .expect("Failed to create tag");
+    common::git::run_git_command(&["checkout", "-b", "feature/x"], path)
+        .expect("Failed to create branch");
+
+    let sha = common::git::get_commit_shas(path)
+        .await
+        .expect("Failed to get commit SHAs")
+        .remove(0);
+
+    let config = Config {
+        build_metadata: Some("{branch}.{sha7}.{dirty}".to_string()),
+        ..Default::default()
+    };
+
+    let result = calculate_version(path, &config).expect("Failed to calculate version");
+    assert_eq!(result.to_string(), format!("1.2.3+feature-x.{}", &sha[..7]));
+
+    // Modify a tracked file to make the working tree dirty
+    std::fs::write(path.join("file.txt"), "a").expect("Failed to write file");
+    common::git::run_git_command(&["add", "file.txt"], path).expect("Failed to add file");
+
+    let result = calculate_version(path, &config).expect("Failed to calculate version");
+    assert_eq!(
+        result.to_string(),
+        format!("1.2.3+feature-x.{}.dirty", &sha[..7])
+    );
+
+    // The working tree doesn't describe another revision
+    let config = Config {
+        revision: Some("feature/x".to_string()),
+        ..config
+    };
+    let result = calculate_version(path, &config).expect("Failed to calculate version");
+    assert_eq!(result.to_string(), format!("1.2.3+feature-x.{}", &sha[..7]));
+}
+
+#[test_case("feature/x y")]
+#[test_case("{unknown}")]
+#[test_case("a..b"; "empty literal identifier")]
+#[tokio::test]
+async fn test_invalid_build_metadata(build_metadata: &str) {
+    use tagver::{calculate_version, Config, TagVerError};
+
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let path = temp_dir.path();
+
+    common::git::ensure_empty_repository_and_commit(path)
+        .await
+        .expect("Failed to create repo");
+
+    let config = Config {
+        build_metadata: Some(build_metadata.to_string()),
+        ..Default::default()
+    };
+
+    let err = calculate_version(path, &config).unwrap_err();
+    assert!(matches!(err, TagVerError::InvalidBuildMetadata(_)));
+}
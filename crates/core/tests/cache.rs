@@ -0,0 +1,158 @@
+//! Result cache tests - cache hits and invalidation after repository changes
+
+use tagver::config::Verbosity;
+use tagver::{calculate_version, Config};
+use tempfile::TempDir;
+
+mod common;
+
+fn cached_config() -> Config {
+    Config {
+        cache: true,
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn test_cache_hit() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let path = temp_dir.path();
+
+    common::git::ensure_empty_repository_and_commit(path)
+        .await
+        .expect("Failed to create repo");
+    common::git::tag(path, "1.0.0")
+        .await
+        .expect("Failed to create tag");
+
+    let result = calculate_version(path, &cached_config()).expect("Failed to calculate version");
+    assert_eq!(result.to_string(), "1.0.0");
+
+    // Tamper with the cached version; an unchanged repository must return it as-is
+    let cache_file = path.join(".git").join(tagver::cache::CACHE_FILE);
+    let contents = std::fs::read_to_string(&cache_file).expect("Cache file not written");
+    let mut lines: Vec<&str> = contents.lines().collect();
+    lines[2] = "9";
+    std::fs::write(&cache_file, lines.join("\n")).expect("Failed to write cache file");
+
+    let result = calculate_version(path, &cached_config()).expect("Failed to calculate version");
+    assert_eq!(result.to_string(), "9.0.0");
+
+    // Options that don't affect the result share the entry
+    let config = Config {
+        verbosity: Verbosity::Debug,
+        ..cached_config()
+    };
+    let result = calculate_version(path, &config).expect("Failed to calculate version");
+    assert_eq!(result.to_string(), "9.0.0");
+
+    // Without the cache option the file is ignored
+    let result = calculate_version(path, &Config::default()).expect("Failed to calculate version");
+    assert_eq!(result.to_string(), "1.0.0");
+
+    // Different options don't share an entry
+    let config = Config {
+        build_metadata: Some("b".to_string()),
+        ..cached_config()
+    };
+    let result = calculate_version(path, &config).expect("Failed to calculate version");
+    assert_eq!(result.to_string(), "1.0.0+b");
+}
+
+#[tokio::test]
+async fn test_cache_invalidation() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let path = temp_dir.path();
+
+    common::git::ensure_empty_repository_and_commit(path)
+        .await
+        .expect("Failed to create repo");
+    common::git::tag(path, "1.0.0")
+        .await
+        .expect("Failed to create tag");
+
+    let result = calculate_version(path, &cached_config()).expect("Failed to calculate version");
+    assert_eq!(result.to_string(), "1.0.0");
+
+    // New commit
+    common::git::commit(path)
+        .await
+        .expect("Failed to create commit");
+    let result = calculate_version(path, &cached_config()).expect("Failed to calculate version");
+    assert_eq!(result.to_string(), "1.0.1-alpha.0.1");
+
+    // New tag
+    common::git::tag(path, "1.1.0")
+        .await
+        .expect("Failed to create tag");
+    let result = calculate_version(path, &cached_config()).expect("Failed to calculate version");
+    assert_eq!(result.to_string(), "1.1.0");
+
+    // Moved tag, as after a forced fetch
+    common::git::run_git_command(&["tag", "-d", "1.1.0"], path).expect("Failed to delete tag");
+    common::git::run_git_command(&["tag", "1.1.0", "HEAD~1"], path).expect("Failed to tag");
+    let result = calculate_version(path, &cached_config()).expect("Failed to calculate version");
+    assert_eq!(result.to_string(), "1.1.1-alpha.0.1");
+
+    // Packed tags
+    common::git::run_git_command(&["pack-refs", "--all"], path).expect("Failed to pack refs");
+    let result = calculate_version(path, &cached_config()).expect("Failed to calculate version");
+    assert_eq!(result.to_string(), "1.1.1-alpha.0.1");
+}
+
+#[tokio::test]
+async fn test_cache_invalidation_after_unshallow() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let origin = temp_dir.path().join("origin");
+    let clone = temp_dir.path().join("clone");
+    std::fs::create_dir(&origin).expect("Failed to create origin directory");
+
+    common::git::ensure_empty_repository_and_commit(&origin)
+        .await
+        .expect("Failed to create repo");
+    common::git::tag(&origin, "1.0.0")
+        .await
+        .expect("Failed to create tag");
+    common::git::commit(&origin)
+        .await
+        .expect("Failed to create commit");
+    common::git::commit(&origin)
+        .await
+        .expect("Failed to create commit");
+
+    // Fetch the tag as a second shallow root, so deepening fetches no new tag
+    let url = format!("file://{}", origin.display());
+    common::git::run_git_command(
+        &["clone", "--depth", "1", &url, clone.to_str().unwrap()],
+        temp_dir.path(),
+    )
+    .expect("Failed to clone");
+    common::git::run_git_command(&["fetch", "--depth", "1", "origin", "tag", "1.0.0"], &clone)
+        .expect("Failed to fetch tag");
+
+    let result = calculate_version(&clone, &cached_config()).expect("Failed to calculate version");
+    assert_eq!(result.to_string(), "0.0.0-alpha.0.1");
+
+    common::git::run_git_command(&["fetch", "--unshallow"], &clone).expect("Failed to unshallow");
+    let result = calculate_version(&clone, &cached_config()).expect("Failed to calculate version");
+    assert_eq!(result.to_string(), "1.0.1-alpha.0.2");
+}
+
+#[tokio::test]
+async fn test_corrupt_cache_is_ignored() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let path = temp_dir.path();
+
+    common::git::ensure_empty_repository_and_commit(path)
+        .await
+        .expect("Failed to create repo");
+    common::git::tag(path, "1.0.0")
+        .await
+        .expect("Failed to create tag");
+
+    let cache_file = path.join(".git").join(tagver::cache::CACHE_FILE);
+    std::fs::write(&cache_file, "garbage").expect("Failed to write cache file");
+
+    let result = calculate_version(path, &cached_config()).expect("Failed to calculate version");
+    assert_eq!(result.to_string(), "1.0.0");
+}
@@ -0,0 +1,60 @@
+//! Commit-graph tests - traversal must agree with and without `.git/objects/info/commit-graph`
+
+use tagver::{calculate_version, Config};
+use tempfile::TempDir;
+
+mod common;
+
+#[tokio::test]
+async fn test_commit_graph_matches_object_lookup() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let path = temp_dir.path();
+
+    common::git::ensure_empty_repository_and_commit(path)
+        .await
+        .expect("Failed to create repo");
+    common::git::tag(path, "1.0.0")
+        .await
+        .expect("Failed to create tag");
+    common::git::run_git_command(&["checkout", "-b", "feature"], path)
+        .expect("Failed to create branch");
+    common::git::commit(path)
+        .await
+        .expect("Failed to create commit");
+    common::git::tag(path, "2.0.0")
+        .await
+        .expect("Failed to create tag");
+    common::git::checkout(path, "main")
+        .await
+        .expect("Failed to checkout main");
+    // A distinct message keeps this commit from being identical to the feature commit
+    common::git::run_git_command(&["commit", "--allow-empty", "-m", "main"], path)
+        .expect("Failed to create commit");
+    common::git::run_git_command(&["merge", "feature", "--no-edit", "--no-ff"], path)
+        .expect("Failed to merge");
+
+    common::git::run_git_command(&["commit-graph", "write", "--reachable"], path)
+        .expect("Failed to write commit-graph");
+    assert!(path.join(".git/objects/info/commit-graph").exists());
+
+    // Commits after the graph was written are not covered by it
+    common::git::commit(path)
+        .await
+        .expect("Failed to create commit");
+    common::git::commit(path)
+        .await
+        .expect("Failed to create commit");
+
+    let with_graph =
+        calculate_version(path, &Config::default()).expect("Failed to calculate version");
+
+    common::git::run_git_command(&["config", "core.commitGraph", "false"], path)
+        .expect("Failed to disable commit-graph");
+    let without_graph =
+        calculate_version(path, &Config::default()).expect("Failed to calculate version");
+
+    // First-parent traversal skips the merged 2.0.0 tag
+    assert_eq!(with_graph.to_string(), "1.0.1-alpha.0.4");
+    assert_eq!(with_graph.to_string(), without_graph.to_string());
+    assert_eq!(with_graph.height, without_graph.height);
+}
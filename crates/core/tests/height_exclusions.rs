@@ -0,0 +1,86 @@
+//! Height exclusion tests - commits skipped by message or author email
+
+use std::path::Path;
+
+use regex::Regex;
+use tagver::config::HeightMetric;
+use tagver::{calculate_version, Config};
+use tempfile::TempDir;
+use test_case::test_case;
+
+mod common;
+
+const DEPENDABOT: &str = "dependabot[bot] <49699333+dependabot[bot]@users.noreply.github.com>";
+
+fn commit(path: &Path, message: &str, author: Option<&str>) {
+    let mut args = vec!["commit", "--allow-empty", "-m", message];
+    if let Some(author) = author {
+        args.extend(["--author", author]);
+    }
+    common::git::run_git_command(&args, path).expect("Failed to create commit");
+}
+
+fn exclusions() -> Config {
+    Config {
+        height_exclude_messages: vec![
+            Regex::new(r"^chore\(deps\)").unwrap(),
+            Regex::new(r"\[skip version\]").unwrap(),
+        ],
+        height_exclude_authors: vec!["*[bot]@users.noreply.github.com".parse().unwrap()],
+        ..Default::default()
+    }
+}
+
+async fn repo_with_tag() -> TempDir {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+
+    common::git::ensure_empty_repository_and_commit(temp_dir.path())
+        .await
+        .expect("Failed to create repo");
+    common::git::tag(temp_dir.path(), "1.0.0")
+        .await
+        .expect("Failed to create tag");
+
+    temp_dir
+}
+
+#[test_case(HeightMetric::FirstParent)]
+#[test_case(HeightMetric::MinVer)]
+#[test_case(HeightMetric::Ancestry)]
+#[test_case(HeightMetric::NoMerges)]
+#[tokio::test]
+async fn test_excluded_commits_do_not_count(metric: HeightMetric) {
+    let temp_dir = repo_with_tag().await;
+    let path = temp_dir.path();
+
+    commit(path, "feat: something useful", None);
+    commit(path, "chore(deps): bump serde from 1.0.1 to 1.0.2", None);
+    commit(path, "Update lockfile", Some(DEPENDABOT));
+    commit(path, "fix: a bug", None);
+    commit(path, "docs: typo [skip version]", None);
+
+    let result = calculate_version(path, &Config::default()).expect("Failed to calculate");
+    assert_eq!(result.to_string(), "1.0.1-alpha.0.5");
+
+    let config = Config {
+        height_metric: metric,
+        ..exclusions()
+    };
+    let result = calculate_version(path, &config).expect("Failed to calculate");
+    assert_eq!(result.to_string(), "1.0.1-alpha.0.2");
+}
+
+#[tokio::test]
+async fn test_excluded_head_has_version_of_parent() {
+    let temp_dir = repo_with_tag().await;
+    let path = temp_dir.path();
+
+    commit(path, "chore(deps): bump serde from 1.0.1 to 1.0.2", None);
+    let result = calculate_version(path, &exclusions()).expect("Failed to calculate");
+    assert_eq!(result.to_string(), "1.0.0");
+
+    commit(path, "feat: something useful", None);
+    commit(path, "Update lockfile", Some(DEPENDABOT));
+    let result = calculate_version(path, &exclusions()).expect("Failed to calculate");
+    assert_eq!(result.to_string(), "1.0.1-alpha.0.1");
+}
@@ -0,0 +1,185 @@
+//! Height format tests - padding, placement, identifiers and substituted values
+
+use tagver::config::{HeightFormat, HeightPlacement, HeightSource, IdentifierValidation};
+use tagver::{calculate_version, Config};
+use tempfile::TempDir;
+use test_case::test_case;
+
+mod common;
+
+async fn repo_with_height(tag_name: &str, height: usize) -> TempDir {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let path = temp_dir.path();
+
+    common::git::ensure_empty_repository_and_commit(path)
+        .await
+        .expect("Failed to create repo");
+    common::git::tag(path, tag_name)
+        .await
+        .expect("Failed to create tag");
+    for _ in 0..height {
+        common::git::commit(path)
+            .await
+            .expect("Failed to create commit");
+    }
+
+    temp_dir
+}
+
+#[test_case("1.0.0", None, "1.0.1-alpha.0.10")]
+#[test_case("1.0.0", Some("dev"), "1.0.1-alpha.0.dev.10")]
+#[test_case("1.0.0-beta.1", Some("dev"), "1.0.0-beta.1.dev.10")]
+#[tokio::test]
+async fn test_prerelease_placement(
+    tag_name: &str,
+    identifier: Option<&str>,
+    expected_version: &str,
+) {
+    let temp_dir = repo_with_height(tag_name, 10).await;
+
+    let config = Config {
+        height_format: HeightFormat {
+            identifier: identifier.map(|s| s.to_string()),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    let result = calculate_version(temp_dir.path(), &config).expect("Failed to calculate version");
+    assert_eq!(result.to_string(), expected_version);
+}
+
+#[test_case("1.0.0", None, "1.0.1-alpha.0+5")]
+#[test_case("1.0.0", Some("b"), "1.0.1-alpha.0+5.b")]
+#[test_case("1.0.0-beta.1", None, "1.0.0-beta.1+5")]
+#[tokio::test]
+async fn test_build_metadata_placement(
+    tag_name: &str,
+    build_metadata: Option<&str>,
+    expected_version: &str,
+) {
+    let temp_dir = repo_with_height(tag_name, 5).await;
+
+    let config = Config {
+        build_metadata: build_metadata.map(|s| s.to_string()),
+        height_format: HeightFormat {
+            placement: HeightPlacement::BuildMetadata,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    let result = calculate_version(temp_dir.path(), &config).expect("Failed to calculate version");
+    assert_eq!(result.to_string(), expected_version);
+}
+
+#[tokio::test]
+async fn test_padding_rejected_in_prerelease() {
+    use tagver::TagVerError;
+
+    let temp_dir = repo_with_height("1.0.0", 5).await;
+
+    let config = Config {
+        height_format: HeightFormat {
+            padding: 4,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    let err = calculate_version(temp_dir.path(), &config).unwrap_err();
+    assert!(matches!(err, TagVerError::InvalidHeightFormat(_)));
+
+    // The identifier validation policy decides
+    let config = Config {
+        identifier_validation: IdentifierValidation::Warn,
+        ..config
+    };
+    let result = calculate_version(temp_dir.path(), &config).expect("Failed to calculate version");
+    assert_eq!(result.to_string(), "1.0.1-alpha.0.0005");
+
+    let config = Config {
+        identifier_validation: IdentifierValidation::Sanitize,
+        ..config
+    };
+    let result = calculate_version(temp_dir.path(), &config).expect("Failed to calculate version");
+    assert_eq!(result.to_string(), "1.0.1-alpha.0.5");
+
+    // Leading zeros are valid in build metadata
+    let config = Config {
+        height_format: HeightFormat {
+            padding: 4,
+            placement: HeightPlacement::BuildMetadata,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    let result = calculate_version(temp_dir.path(), &config).expect("Failed to calculate version");
+    assert_eq!(result.to_string(), "1.0.1-alpha.0+0005");
+}
+
+#[tokio::test]
+async fn test_build_metadata_placement_on_tag() {
+    let temp_dir = repo_with_height("1.0.0+a", 0).await;
+
+    let config = Config {
+        height_format: HeightFormat {
+            placement: HeightPlacement::BuildMetadata,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    // No height is written when exactly on a tag
+    let result = calculate_version(temp_dir.path(), &config).expect("Failed to calculate version");
+    assert_eq!(result.to_string(), "1.0.0+a");
+}
+
+#[tokio::test]
+async fn test_commit_count_source() {
+    let temp_dir = repo_with_height("1.0.0", 2).await;
+
+    let config = Config {
+        height_format: HeightFormat {
+            source: HeightSource::CommitCount,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    // Three commits in total, two of them since the tag
+    let result = calculate_version(temp_dir.path(), &config).expect("Failed to calculate version");
+    assert_eq!(result.to_string(), "1.0.1-alpha.0.3");
+    assert_eq!(result.height, 2);
+}
+
+#[tokio::test]
+async fn test_timestamp_source() {
+    let temp_dir = repo_with_height("1.0.0", 1).await;
+    let path = temp_dir.path();
+
+    let output = std::process::Command::new("git")
+        .args([
+            "log",
+            "-1",
+            "--date=format-local:%Y%m%d%H%M%S",
+            "--format=%cd",
+        ])
+        .env("TZ", "UTC")
+        .current_dir(path)
+        .output()
+        .expect("Failed to run git log");
+    let timestamp = String::from_utf8_lossy(&output.stdout).trim().to_string();
+
+    let config = Config {
+        height_format: HeightFormat {
+            source: HeightSource::Timestamp,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    let result = calculate_version(path, &config).expect("Failed to calculate version");
+    assert_eq!(result.to_string(), format!("1.0.1-alpha.0.{}", timestamp));
+}
@@ -0,0 +1,106 @@
+//! Height metric tests - first-parent, MinVer, ancestry and merge-free counting
+
+use std::path::Path;
+
+use tagver::config::HeightMetric;
+use tagver::{calculate_version, Config};
+use tempfile::TempDir;
+use test_case::test_case;
+
+mod common;
+
+fn commit(path: &Path, message: &str) {
+    common::git::run_git_command(&["commit", "--allow-empty", "-m", message], path)
+        .expect("Failed to create commit");
+}
+
+/// `1.0.0` on the root commit of `main`, then a `feature` branch with three commits
+/// merged into `main` after one more commit there.
+async fn repo_with_merged_branch() -> TempDir {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let path = temp_dir.path();
+
+    common::git::ensure_empty_repository_and_commit(path)
+        .await
+        .expect("Failed to create repo");
+    common::git::tag(path, "1.0.0")
+        .await
+        .expect("Failed to create tag");
+    common::git::run_git_command(&["checkout", "-b", "feature"], path)
+        .expect("Failed to create branch");
+    for message in ["feature 1", "feature 2", "feature 3"] {
+        commit(path, message);
+    }
+    common::git::checkout(path, "main")
+        .await
+        .expect("Failed to checkout");
+    commit(path, "main 1");
+    common::git::run_git_command(
+        &["merge", "--no-ff", "-m", "Merge feature", "feature"],
+        path,
+    )
+    .expect("Failed to merge");
+
+    temp_dir
+}
+
+#[test_case(HeightMetric::FirstParent, "1.0.1-alpha.0.2")]
+#[test_case(HeightMetric::MinVer, "1.0.1-alpha.0.2")]
+#[test_case(HeightMetric::Ancestry, "1.0.1-alpha.0.5")]
+#[test_case(HeightMetric::NoMerges, "1.0.1-alpha.0.4")]
+#[tokio::test]
+async fn test_height_metric(metric: HeightMetric, expected_version: &str) {
+    let temp_dir = repo_with_merged_branch().await;
+
+    let config = Config {
+        height_metric: metric,
+        ..Default::default()
+    };
+    let result = calculate_version(temp_dir.path(), &config).expect("Failed to calculate");
+    assert_eq!(result.to_string(), expected_version);
+}
+
+#[test_case(HeightMetric::FirstParent, "1.0.1-alpha.0.2")]
+#[test_case(HeightMetric::MinVer, "1.1.1-alpha.0.2")]
+#[tokio::test]
+async fn test_height_metric_with_tag_on_merged_branch(metric: HeightMetric, expected: &str) {
+    let temp_dir = repo_with_merged_branch().await;
+    common::git::run_git_command(&["tag", "1.1.0", "feature~1"], temp_dir.path())
+        .expect("Failed to create tag");
+
+    let config = Config {
+        height_metric: metric,
+        ..Default::default()
+    };
+    let result = calculate_version(temp_dir.path(), &config).expect("Failed to calculate");
+    assert_eq!(result.to_string(), expected);
+}
+
+#[test_case(HeightMetric::FirstParent)]
+#[test_case(HeightMetric::MinVer)]
+#[test_case(HeightMetric::Ancestry)]
+#[test_case(HeightMetric::NoMerges)]
+#[tokio::test]
+async fn test_height_metric_on_tag_and_without_tags(metric: HeightMetric) {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let path = temp_dir.path();
+    let config = Config {
+        height_metric: metric,
+        ..Default::default()
+    };
+
+    common::git::ensure_empty_repository_and_commit(path)
+        .await
+        .expect("Failed to create repo");
+    commit(path, "second");
+    commit(path, "third");
+    let result = calculate_version(path, &config).expect("Failed to calculate");
+    assert_eq!(result.to_string(), "0.0.0-alpha.0.2");
+
+    common::git::tag(path, "1.0.0")
+        .await
+        .expect("Failed to create tag");
+    let result = calculate_version(path, &config).expect("Failed to calculate");
+    assert_eq!(result.to_string(), "1.0.0");
+    assert_eq!(result.height, 0);
+}
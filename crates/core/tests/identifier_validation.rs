@@ -0,0 +1,84 @@
+//! Identifier validation tests - synthesized versions must round-trip through `semver`
+
+use tagver::config::IdentifierValidation;
+use tagver::{calculate_version, Config, TagVerError};
+use tempfile::TempDir;
+use test_case::test_case;
+
+mod common;
+
+async fn repo_after_tag(tag_name: &str) -> TempDir {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let path = temp_dir.path();
+
+    common::git::ensure_empty_repository_and_commit(path)
+        .await
+        .expect("Failed to create repo");
+    common::git::tag(path, tag_name)
+        .await
+        .expect("Failed to create tag");
+    common::git::commit(path)
+        .await
+        .expect("Failed to create commit");
+
+    temp_dir
+}
+
+#[test_case(&["alpha", "01"], None, "1.0.1-alpha.1.1")]
+#[test_case(&["feature/x", ""], None, "1.0.1-feature-x.1")]
+#[test_case(&["alpha", "0"], Some("feature/x y"), "1.0.1-alpha.0.1+feature-x-y")]
+#[test_case(&["alpha", "0"], Some("a..b"), "1.0.1-alpha.0.1+a.b")]
+#[test_case(&["été"], None, "1.0.1--t-.1")]
+#[tokio::test]
+async fn test_sanitize(prerelease: &[&str], build_metadata: Option<&str>, expected_version: &str) {
+    let temp_dir = repo_after_tag("1.0.0").await;
+
+    let config = Config {
+        default_prerelease_identifiers: prerelease.iter().map(|s| s.to_string()).collect(),
+        build_metadata: build_metadata.map(|s| s.to_string()),
+        identifier_validation: IdentifierValidation::Sanitize,
+        ..Default::default()
+    };
+
+    let result = calculate_version(temp_dir.path(), &config).expect("Failed to calculate version");
+    assert_eq!(result.to_string(), expected_version);
+
+    // Sanitized output always round-trips
+    let parsed = semver::Version::parse(&result.to_string()).expect("Not valid SemVer");
+    assert_eq!(parsed.to_string(), result.to_string());
+}
+
+#[test_case(&["alpha", "01"], None)]
+#[test_case(&["alpha", ""], None)]
+#[test_case(&["alpha", "0"], Some("feature/x y"))]
+#[test_case(&["alpha", "0"], Some("a..b"))]
+#[tokio::test]
+async fn test_error(prerelease: &[&str], build_metadata: Option<&str>) {
+    let temp_dir = repo_after_tag("1.0.0").await;
+
+    let config = Config {
+        default_prerelease_identifiers: prerelease.iter().map(|s| s.to_string()).collect(),
+        build_metadata: build_metadata.map(|s| s.to_string()),
+        ..Default::default()
+    };
+
+    let err = calculate_version(temp_dir.path(), &config).unwrap_err();
+    assert!(matches!(
+        err,
+        TagVerError::InvalidPrerelease(_) | TagVerError::InvalidBuildMetadata(_)
+    ));
+}
+
+#[tokio::test]
+async fn test_warn_keeps_identifiers() {
+    let temp_dir = repo_after_tag("1.0.0").await;
+
+    let config = Config {
+        default_prerelease_identifiers: vec!["alpha".into(), "01".into()],
+        identifier_validation: IdentifierValidation::Warn,
+        ..Default::default()
+    };
+
+    let result = calculate_version(temp_dir.path(), &config).expect("Failed to calculate version");
+    assert_eq!(result.to_string(), "1.0.1-alpha.01.1");
+}
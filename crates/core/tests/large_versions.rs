@@ -0,0 +1,63 @@
+//! Large version component tests - date-based majors and u64 overflow
+
+use tagver::{calculate_version, Config, TagVerError, VersionPart};
+use tempfile::TempDir;
+use test_case::test_case;
+
+mod common;
+
+#[test_case("20240101.0.0", 0, "20240101.0.0")]
+#[test_case("20240101.0.0", 1, "20240101.0.1-alpha.0.1")]
+#[test_case("4294967296.0.0", 0, "4294967296.0.0")]
+#[test_case(
+    "4294967296.4294967296.4294967296",
+    1,
+    "4294967296.4294967296.4294967297-alpha.0.1"
+)]
+#[tokio::test]
+async fn test_large_components(tag_name: &str, height: usize, expected_version: &str) {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let path = temp_dir.path();
+
+    common::git::ensure_empty_repository_and_commit(path)
+        .await
+        .expect("Failed to create repo");
+    common::git::tag(path, tag_name)
+        .await
+        .expect("Failed to create tag");
+    for _ in 0..height {
+        common::git::commit(path)
+            .await
+            .expect("Failed to create commit");
+    }
+
+    let result = calculate_version(path, &Config::default()).expect("Failed to calculate version");
+    assert_eq!(result.to_string(), expected_version);
+}
+
+#[test_case("1.2.18446744073709551615", VersionPart::Patch)]
+#[test_case("1.18446744073709551615.0", VersionPart::Minor)]
+#[test_case("18446744073709551615.0.0", VersionPart::Major)]
+#[tokio::test]
+async fn test_increment_overflow(tag_name: &str, auto_increment: VersionPart) {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let path = temp_dir.path();
+
+    common::git::ensure_empty_repository_and_commit(path)
+        .await
+        .expect("Failed to create repo");
+    common::git::tag(path, tag_name)
+        .await
+        .expect("Failed to create tag");
+    common::git::commit(path)
+        .await
+        .expect("Failed to create commit");
+
+    let config = Config {
+        auto_increment,
+        ..Default::default()
+    };
+
+    let err = calculate_version(path, &config).unwrap_err();
+    assert!(matches!(err, TagVerError::VersionOverflow(_)));
+}
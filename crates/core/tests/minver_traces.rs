@@ -0,0 +1,299 @@
+//! MinVer trace tests - replays the trace logs in `fixtures/log.*.txt` recorded by MinVer
+//!
+//! Each log holds the output of the git commands MinVer ran: the commit graph from
+//! `git log --pretty=format:"%H %P"` and the tags from `git show-ref --tags --dereference`.
+//! The graph and tags are rebuilt in a temporary repository with the same shape and in a
+//! `MemoryGraph`, and the version each backend calculates is compared with the
+//! `Calculated version` MinVer logged. The minimum major minor is read from the log when
+//! MinVer mentions it. Adding a case only requires dropping another `log.*.txt` file into
+//! `fixtures`.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use regex::Regex;
+use tagver::config::{Backend, HeightMetric, MajorMinor};
+use tagver::git::calculate_version_with;
+use tagver::graph::MemoryGraph;
+use tagver::{calculate_version, Config};
+use tempfile::TempDir;
+
+mod common;
+
+const GIT_LOG: &str = r#"git log --pretty=format:"%H %P""#;
+const GIT_SHOW_REF: &str = "git show-ref --tags --dereference";
+
+/// A recorded MinVer run.
+struct Trace {
+    /// Commits with their parents, HEAD first.
+    commits: Vec<(String, Vec<String>)>,
+    /// Tag names with the commit they point to.
+    tags: Vec<(String, String)>,
+    minimum_major_minor: Option<MajorMinor>,
+    expected_version: String,
+}
+
+impl Trace {
+    fn parse(log: &str) -> Result<Self, String> {
+        let outputs = git_outputs(log);
+
+        let commits: Vec<(String, Vec<String>)> = outputs
+            .get(GIT_LOG)
+            .ok_or("no `git log` output")?
+            .iter()
+            .map(|line| {
+                let mut ids = line.split_whitespace().map(str::to_string);
+                let id = ids.next().expect("lines are not empty");
+                (id, ids.collect())
+            })
+            .collect();
+        if commits.is_empty() {
+            return Err("empty `git log` output".to_string());
+        }
+
+        // Dereferenced annotated tags (`name^{}`) point to the commit, and replace the tag object
+        let mut tags: Vec<(String, String)> = Vec::new();
+        for line in outputs
+            .get(GIT_SHOW_REF)
+            .ok_or("no `git show-ref` output")?
+        {
+            let (id, name) = line
+                .split_once(' ')
+                .ok_or_else(|| format!("invalid ref line '{}'", line))?;
+            let name = name.trim_start_matches("refs/tags/");
+            let (name, id) = (name.trim_end_matches("^{}"), id.to_string());
+            match tags.iter_mut().find(|(existing, _)| existing == name) {
+                Some(tag) => tag.1 = id,
+                None => tags.push((name.to_string(), id)),
+            }
+        }
+
+        let minimum_major_minor = Regex::new(r"minimum major minor (\d+\.\d+)")
+            .unwrap()
+            .captures(log)
+            .map(|captures| MajorMinor::parse(&captures[1]))
+            .transpose()
+            .map_err(|e| e.to_string())?;
+
+        let expected_version = Regex::new(r"Calculated version (\S+)\.\s*$")
+            .unwrap()
+            .captures(log.trim_end())
+            .ok_or("no `Calculated version` message")?[1]
+            .to_string();
+
+        Ok(Self {
+            commits,
+            tags,
+            minimum_major_minor,
+            expected_version,
+        })
+    }
+
+    /// Rebuild the commit graph and tags in an empty repository, with HEAD on the first commit.
+    async fn build(&self, path: &Path) {
+        common::git::ensure_empty_repository(path)
+            .await
+            .expect("Failed to create repo");
+
+        let empty_tree = git_output(&["hash-object", "-t", "tree", "-w", "/dev/null"], path);
+        let parents: HashMap<&str, &[String]> = self
+            .commits
+            .iter()
+            .map(|(id, parents)| (id.as_str(), parents.as_slice()))
+            .collect();
+
+        let mut created = HashMap::new();
+        for (id, _) in &self.commits {
+            create_commit(id, &parents, &empty_tree, path, &mut created);
+        }
+
+        let head = &created[self.commits[0].0.as_str()];
+        common::git::run_git_command(&["update-ref", "refs/heads/main", head], path)
+            .expect("Failed to update main");
+        for (name, id) in &self.tags {
+            let target = created
+                .get(id.as_str())
+                .unwrap_or_else(|| panic!("Tag '{}' points to unknown commit {}", name, id));
+            common::git::run_git_command(&["tag", name, target], path)
+                .expect("Failed to create tag");
+        }
+    }
+}
+
+impl Trace {
+    /// The commit graph and tags as a [`MemoryGraph`], with HEAD on the first commit.
+    fn memory_graph(&self) -> MemoryGraph {
+        fn add(id: &str, trace: &Trace, graph: &mut MemoryGraph) {
+            if graph.id(id).is_some() {
+                return;
+            }
+            let (_, parents) = trace
+                .commits
+                .iter()
+                .find(|(commit, _)| commit == id)
+                .unwrap_or_else(|| panic!("Unknown commit {}", id));
+            for parent in parents {
+                add(parent, trace, graph);
+            }
+            let parents: Vec<&str> = parents.iter().map(String::as_str).collect();
+            graph.commit(id, &parents);
+        }
+
+        let mut graph = MemoryGraph::new();
+        for (id, _) in &self.commits {
+            add(id, self, &mut graph);
+        }
+        graph.set_head(&self.commits[0].0);
+        for (name, id) in &self.tags {
+            graph.tag(name, id);
+        }
+        graph
+    }
+}
+
+/// The stdout of each git command in the log, by command line.
+fn git_outputs(log: &str) -> HashMap<&str, Vec<&str>> {
+    let mut outputs = HashMap::new();
+    let mut command = None;
+    let mut stdout: Option<Vec<&str>> = None;
+
+    for line in log.lines() {
+        if let Some(running) = line.strip_prefix("Trace: Running Git: ") {
+            command = Some(running.trim());
+        } else if line.starts_with("Trace: Git stdout:") {
+            stdout = Some(Vec::new());
+        } else if line.starts_with("Trace: Git stderr:") {
+            if let (Some(command), Some(lines)) = (command.take(), stdout.take()) {
+                outputs.insert(command, lines);
+            }
+        } else if let Some(lines) = stdout.as_mut() {
+            if !line.trim().is_empty() {
+                lines.push(line.trim());
+            }
+        }
+    }
+
+    outputs
+}
+
+/// Create a commit and its ancestors, mapping the recorded ids to the new commit ids. The
+/// recorded id is the message, so commits with the same parents still differ.
+fn create_commit(
+    id: &str,
+    parents: &HashMap<&str, &[String]>,
+    tree: &str,
+    path: &Path,
+    created: &mut HashMap<String, String>,
+) -> String {
+    if let Some(sha) = created.get(id) {
+        return sha.clone();
+    }
+
+    let mut args = vec!["commit-tree".to_string(), tree.to_string()];
+    for parent in parents[id].iter() {
+        let parent = create_commit(parent, parents, tree, path, created);
+        args.extend(["-p".to_string(), parent]);
+    }
+    args.extend(["-m".to_string(), id.to_string()]);
+
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    let sha = git_output(&args, path);
+    created.insert(id.to_string(), sha.clone());
+    sha
+}
+
+fn git_output(args: &[&str], path: &Path) -> String {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(path)
+        .output()
+        .expect("Failed to run git");
+    assert!(
+        output.status.success(),
+        "git {} failed: {}",
+        args.join(" "),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8_lossy(&output.stdout).trim().to_string()
+}
+
+fn trace_files() -> Vec<PathBuf> {
+    let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    let mut files: Vec<PathBuf> = std::fs::read_dir(fixtures)
+        .expect("Failed to read fixtures")
+        .map(|entry| entry.expect("Failed to read fixture").path())
+        .filter(|path| {
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            name.starts_with("log.") && name.ends_with(".txt")
+        })
+        .collect();
+    files.sort();
+    files
+}
+
+#[tokio::test]
+async fn test_minver_traces() {
+    let files = trace_files();
+    assert!(!files.is_empty(), "No MinVer traces found");
+
+    let mut failures = Vec::new();
+    for file in &files {
+        let name = file.file_name().unwrap().to_string_lossy();
+        let log = std::fs::read_to_string(file).expect("Failed to read trace");
+        let trace = match Trace::parse(&log) {
+            Ok(trace) => trace,
+            Err(e) => {
+                failures.push(format!("{}: {}", name, e));
+                continue;
+            }
+        };
+
+        let temp_dir = TempDir::new().expect("Failed to create temp directory");
+        trace.build(temp_dir.path()).await;
+
+        let config = Config {
+            height_metric: HeightMetric::MinVer,
+            minimum_major_minor: trace.minimum_major_minor.clone(),
+            ..Default::default()
+        };
+        let git_config = Config {
+            backend: Backend::Git,
+            ..config.clone()
+        };
+        let graph = trace.memory_graph();
+
+        let results = [
+            (
+                "gix",
+                calculate_version(temp_dir.path(), &config).map(|r| r.version),
+            ),
+            (
+                "git",
+                calculate_version(temp_dir.path(), &git_config).map(|r| r.version),
+            ),
+            (
+                "memory",
+                calculate_version_with(&graph, &graph, &config).map(|(version, _, _)| version),
+            ),
+        ];
+        for (backend, result) in results {
+            match result {
+                Ok(version) if version.to_string() == trace.expected_version => {}
+                Ok(version) => failures.push(format!(
+                    "{} ({}): expected {}, calculated {}",
+                    name, backend, trace.expected_version, version
+                )),
+                Err(e) => failures.push(format!("{} ({}): {}", name, backend, e)),
+            }
+        }
+    }
+
+    assert!(
+        failures.is_empty(),
+        "{} of {} MinVer traces differ:\n{}",
+        failures.len(),
+        files.len(),
+        failures.join("\n")
+    );
+}
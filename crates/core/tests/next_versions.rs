@@ -0,0 +1,158 @@
+//! Next version tests - previews of each bump kind and the Conventional Commits bump
+
+use tagver::config::{Backend, VersionPart};
+use tagver::graph::{CommitInfo, MemoryGraph};
+use tagver::next::{commit_bump, next_versions_with, BumpKind};
+use tagver::{next_versions, Config};
+use tempfile::TempDir;
+use test_case::test_case;
+
+mod common;
+
+fn graph_with_messages(tag: &str, messages: &[&str]) -> MemoryGraph {
+    let mut graph = MemoryGraph::new();
+    graph.commit("root", &[]);
+    graph.tag(tag, "root");
+    let mut parent = "root".to_string();
+    for message in messages {
+        graph.commit_with(
+            message,
+            &[&parent],
+            CommitInfo {
+                message: message.to_string(),
+                ..Default::default()
+            },
+        );
+        parent = message.to_string();
+    }
+    graph
+}
+
+#[test_case("1.2.3", "2.0.0", "1.3.0", "1.2.4", "1.2.4-alpha.0"; "release")]
+#[test_case("1.2.4-rc.1", "2.0.0", "1.3.0", "1.2.4", "1.2.4-rc.2"; "release candidate")]
+#[test_case("2.0.0-beta.3", "2.0.0", "2.0.0", "2.0.0", "2.0.0-beta.4"; "major prerelease")]
+fn test_bumps(tag: &str, major: &str, minor: &str, patch: &str, prerelease: &str) {
+    let graph = graph_with_messages(tag, &["fix: something"]);
+    let next = next_versions_with(&graph, &graph, &Config::default()).unwrap();
+
+    assert_eq!(next.base_tag.as_deref(), Some(tag));
+    assert_eq!(next.base.to_string(), tag);
+    assert_eq!(next.height, 1);
+    assert_eq!(next.major.to_string(), major);
+    assert_eq!(next.minor.to_string(), minor);
+    assert_eq!(next.patch.to_string(), patch);
+    assert_eq!(next.prerelease.to_string(), prerelease);
+}
+
+#[test]
+fn test_prerelease_of_release_uses_config() {
+    let graph = graph_with_messages("1.2.3", &[]);
+    let config = Config {
+        auto_increment: VersionPart::Minor,
+        default_prerelease_identifiers: vec!["preview".to_string(), "0".to_string()],
+        ..Default::default()
+    };
+
+    let next = next_versions_with(&graph, &graph, &config).unwrap();
+    assert_eq!(next.prerelease.to_string(), "1.3.0-preview.0");
+    assert_eq!(next.height, 0);
+    assert_eq!(next.conventional, None);
+}
+
+#[test]
+fn test_without_tags_or_commits() {
+    let mut graph = MemoryGraph::new();
+    let next = next_versions_with(&graph, &graph, &Config::default()).unwrap();
+    assert_eq!(next.base_tag, None);
+    assert_eq!(next.base.to_string(), "0.0.0");
+    assert_eq!(next.minor.to_string(), "0.1.0");
+    assert_eq!(next.conventional, None);
+
+    graph.commit("root", &[]);
+    let next = next_versions_with(&graph, &graph, &Config::default()).unwrap();
+    assert_eq!(next.base_tag, None);
+    assert_eq!(next.patch.to_string(), "0.0.1");
+    assert_eq!(next.conventional, Some(BumpKind::Patch));
+}
+
+#[test_case(&["docs: readme", "fix(cli): crash"], BumpKind::Patch; "fixes")]
+#[test_case(&["fix: crash", "feat: next command", "chore: deps"], BumpKind::Minor; "feature")]
+#[test_case(&["feat(api)!: remove v1"], BumpKind::Major; "breaking header")]
+#[test_case(&["fix: parse\n\nBREAKING CHANGE: tags need a prefix"], BumpKind::Major; "breaking footer")]
+#[test_case(&["Update README"], BumpKind::Patch; "not conventional")]
+fn test_conventional(messages: &[&str], expected: BumpKind) {
+    let graph = graph_with_messages("1.0.0", messages);
+    let next = next_versions_with(&graph, &graph, &Config::default()).unwrap();
+
+    assert_eq!(next.conventional, Some(expected));
+    assert_eq!(
+        next.get(expected).to_string(),
+        match expected {
+            BumpKind::Major => "2.0.0",
+            BumpKind::Minor => "1.1.0",
+            _ => "1.0.1",
+        }
+    );
+}
+
+#[test]
+fn test_commit_bump() {
+    assert_eq!(commit_bump("feat: add"), BumpKind::Minor);
+    assert_eq!(commit_bump("Feat(scope): add"), BumpKind::Minor);
+    assert_eq!(commit_bump("feature: add"), BumpKind::Patch);
+    assert_eq!(commit_bump("feat:missing space"), BumpKind::Patch);
+    assert_eq!(commit_bump("chore!: drop MSRV"), BumpKind::Major);
+    assert_eq!(
+        commit_bump("feat: add\n\nBREAKING-CHANGE: renamed"),
+        BumpKind::Major
+    );
+    // The footer must start the line, and isn't in the header
+    assert_eq!(
+        commit_bump("fix: mention BREAKING CHANGE: here"),
+        BumpKind::Patch
+    );
+}
+
+#[tokio::test]
+async fn test_merged_branch_commits_count() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let path = temp_dir.path();
+
+    common::git::ensure_empty_repository_and_commit(path)
+        .await
+        .expect("Failed to create repo");
+    common::git::tag(path, "v1.0.0")
+        .await
+        .expect("Failed to create tag");
+    common::git::run_git_command(&["checkout", "-b", "feature"], path)
+        .expect("Failed to create branch");
+    common::git::run_git_command(
+        &["commit", "--allow-empty", "-m", "feat: on a branch"],
+        path,
+    )
+    .expect("Failed to commit");
+    common::git::checkout(path, "main")
+        .await
+        .expect("Failed to checkout");
+    common::git::run_git_command(&["commit", "--allow-empty", "-m", "fix: on main"], path)
+        .expect("Failed to commit");
+    common::git::run_git_command(
+        &["merge", "--no-ff", "-m", "Merge feature", "feature"],
+        path,
+    )
+    .expect("Failed to merge");
+
+    for backend in [Backend::Gix, Backend::Git] {
+        let config = Config {
+            tag_prefix: "v".to_string(),
+            backend,
+            ..Default::default()
+        };
+        let next = next_versions(path, &config).expect("Failed to preview");
+
+        assert_eq!(next.base_tag.as_deref(), Some("v1.0.0"));
+        assert_eq!(next.height, 2);
+        assert_eq!(next.conventional, Some(BumpKind::Minor));
+        assert_eq!(next.get(BumpKind::Minor).to_string(), "1.1.0");
+    }
+}
@@ -0,0 +1,227 @@
+//! Property tests - version synthesis invariants over random commit graphs and tag sets
+//!
+//! Graphs are built as a `MemoryGraph`: commit `i` has one or two parents among the
+//! commits before it, so every graph is a DAG with HEAD on the last commit.
+
+use proptest::prelude::*;
+use semver::Version;
+use tagver::config::{HeightMetric, MajorMinor, VersionPart};
+use tagver::git::calculate_version_with;
+use tagver::graph::{CommitGraph, MemoryGraph};
+use tagver::Config;
+
+/// A random commit graph with version tags.
+#[derive(Debug, Clone)]
+struct Scenario {
+    /// The parents of each commit, by index; the first commit is the root.
+    parents: Vec<Vec<usize>>,
+    /// Tagged commits with their version.
+    tags: Vec<(usize, Version)>,
+}
+
+impl Scenario {
+    fn name(index: usize) -> String {
+        format!("c{}", index)
+    }
+
+    fn graph(&self) -> MemoryGraph {
+        let mut graph = MemoryGraph::new();
+        for (index, parents) in self.parents.iter().enumerate() {
+            let parents: Vec<String> = parents.iter().map(|&p| Self::name(p)).collect();
+            let parents: Vec<&str> = parents.iter().map(String::as_str).collect();
+            graph.commit(&Self::name(index), &parents);
+        }
+        for (index, version) in &self.tags {
+            graph.tag(&version.to_string(), &Self::name(*index));
+        }
+        graph
+    }
+
+    /// The highest tag on `index`, if any.
+    fn tag_on(&self, index: usize) -> Option<&Version> {
+        self.tags
+            .iter()
+            .filter(|(tagged, _)| *tagged == index)
+            .map(|(_, version)| version)
+            .max_by(|a, b| a.cmp_precedence(b))
+    }
+
+    /// The tag found by following first parents from HEAD, which every height metric
+    /// reaches.
+    fn first_parent_tag(&self) -> Option<&Version> {
+        let mut index = self.parents.len() - 1;
+        loop {
+            if let Some(version) = self.tag_on(index) {
+                return Some(version);
+            }
+            index = *self.parents[index].first()?;
+        }
+    }
+}
+
+/// The calculated version as a `semver::Version`, which compares by SemVer precedence.
+fn parse(version: &tagver::Version) -> Version {
+    Version::parse(&version.to_string())
+        .unwrap_or_else(|e| panic!("{} is not SemVer: {}", version, e))
+}
+
+fn version() -> impl Strategy<Value = Version> {
+    (
+        0u64..4,
+        0u64..4,
+        0u64..4,
+        prop::option::of(prop::sample::select(vec!["alpha.1", "beta.2", "rc.1", "0"])),
+    )
+        .prop_map(|(major, minor, patch, pre)| {
+            let pre = pre.map(|pre| format!("-{}", pre)).unwrap_or_default();
+            Version::parse(&format!("{}.{}.{}{}", major, minor, patch, pre)).unwrap()
+        })
+}
+
+fn scenario() -> impl Strategy<Value = Scenario> {
+    (1usize..16)
+        .prop_flat_map(|count| {
+            let parents = (0..count)
+                .map(|index| match index {
+                    0 => Just(Vec::new()).boxed(),
+                    _ => prop::collection::vec(0..index, 1..=2)
+                        .prop_map(|mut parents| {
+                            parents.dedup();
+                            parents
+                        })
+                        .boxed(),
+                })
+                .collect::<Vec<_>>();
+            let tags = prop::collection::vec((0..count, version()), 0..5);
+            (parents, tags)
+        })
+        .prop_map(|(parents, mut tags)| {
+            // A tag name is unique, so each version is used once
+            let mut seen = Vec::new();
+            tags.retain(|(_, version)| {
+                let unique = !seen.contains(version);
+                seen.push(version.clone());
+                unique
+            });
+            Scenario { parents, tags }
+        })
+}
+
+fn config() -> impl Strategy<Value = Config> {
+    (
+        prop::sample::select(vec![
+            HeightMetric::FirstParent,
+            HeightMetric::MinVer,
+            HeightMetric::Ancestry,
+            HeightMetric::NoMerges,
+        ]),
+        prop::sample::select(vec![
+            VersionPart::Major,
+            VersionPart::Minor,
+            VersionPart::Patch,
+        ]),
+        prop::option::of((0u64..5, 0u64..5)),
+        any::<bool>(),
+    )
+        .prop_map(|(metric, part, minimum, ignore_height)| Config {
+            height_metric: metric,
+            auto_increment: part,
+            minimum_major_minor: minimum.map(|(major, minor)| MajorMinor { major, minor }),
+            ignore_height,
+            ..Default::default()
+        })
+}
+
+proptest! {
+    #[test]
+    fn version_is_never_lower_than_base_tag(scenario in scenario(), config in config()) {
+        let graph = scenario.graph();
+        let (version, _, _) = calculate_version_with(&graph, &graph, &config).unwrap();
+
+        if let Some(base) = scenario.first_parent_tag() {
+            prop_assert!(
+                parse(&version).cmp_precedence(base).is_ge(),
+                "{} is lower than the base tag {}",
+                version,
+                base
+            );
+        }
+    }
+
+    #[test]
+    fn tagged_head_gives_tag(
+        scenario in scenario(),
+        config in config(),
+        metadata in prop::option::of("[a-z0-9]{1,8}"),
+    ) {
+        prop_assume!(!scenario.tags.is_empty());
+        let (index, _) = scenario.tags[0];
+        let tag = scenario.tag_on(index).unwrap();
+
+        let mut graph = scenario.graph();
+        graph.set_head(&Scenario::name(index));
+        let config = Config {
+            minimum_major_minor: None,
+            build_metadata: metadata.clone(),
+            ..config
+        };
+
+        let (version, height, is_from_tag) =
+            calculate_version_with(&graph, &graph, &config).unwrap();
+        prop_assert_eq!(height, 0);
+        prop_assert!(is_from_tag);
+        prop_assert_eq!(&version.build_metadata, &metadata);
+        let mut version = parse(&version);
+        version.build = semver::BuildMetadata::EMPTY;
+        prop_assert_eq!(&version, tag);
+    }
+
+    #[test]
+    fn output_parses_as_semver(
+        scenario in scenario(),
+        config in config(),
+        metadata in prop::option::of("[a-z0-9]{1,8}"),
+    ) {
+        let graph = scenario.graph();
+        let config = Config {
+            build_metadata: metadata,
+            ..config
+        };
+        let (version, _, _) = calculate_version_with(&graph, &graph, &config).unwrap();
+
+        let parsed = Version::parse(&version.to_string());
+        prop_assert!(parsed.is_ok(), "{} is not SemVer: {:?}", version, parsed);
+        prop_assert_eq!(parsed.unwrap().to_string(), version.to_string());
+    }
+
+    #[test]
+    fn minimum_major_minor_is_respected(scenario in scenario(), config in config()) {
+        let graph = scenario.graph();
+        let (version, _, is_from_tag) = calculate_version_with(&graph, &graph, &config).unwrap();
+
+        // Like MinVer, the version of a tagged commit is used as is
+        if let (Some(minimum), false) = (&config.minimum_major_minor, is_from_tag) {
+            prop_assert!(
+                (version.major, version.minor) >= (minimum.major, minimum.minor),
+                "{} is lower than the minimum {}.{}",
+                version,
+                minimum.major,
+                minimum.minor
+            );
+        }
+    }
+
+    #[test]
+    fn results_are_deterministic(scenario in scenario(), config in config()) {
+        let graph = scenario.graph();
+        let first = calculate_version_with(&graph, &graph, &config).unwrap();
+        let second = calculate_version_with(&graph, &graph, &config).unwrap();
+        prop_assert_eq!(format!("{:?}", first), format!("{:?}", second));
+
+        // A graph rebuilt from the same scenario gives the same result
+        let rebuilt = scenario.graph();
+        prop_assert_eq!(rebuilt.resolve(None).unwrap(), graph.resolve(None).unwrap());
+        let third = calculate_version_with(&rebuilt, &rebuilt, &config).unwrap();
+        prop_assert_eq!(format!("{:?}", first), format!("{:?}", third));
+    }
+}
@@ -0,0 +1,150 @@
+//! Remote tag tests - tags fetched from a bare remote into a clone without tags
+#![cfg(feature = "remote-tags")]
+
+use std::path::{Path, PathBuf};
+
+use tagver::{calculate_version, list_tags, Config, TagVerError};
+use tempfile::TempDir;
+
+mod common;
+
+fn local_tags(path: &Path) -> Vec<String> {
+    let output = std::process::Command::new("git")
+        .args(["tag", "--list"])
+        .current_dir(path)
+        .output()
+        .expect("Failed to list tags");
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::to_string)
+        .collect()
+}
+
+/// A bare remote with `1.0.0` (lightweight) and `1.1.0` (annotated) on `main`, and `2.0.0`
+/// on a `next` branch, cloned without tags and without `next`.
+async fn clone_without_tags() -> (TempDir, PathBuf) {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let path = temp_dir.path().join("work");
+    let remote = temp_dir.path().join("remote.git");
+    let clone = temp_dir.path().join("clone");
+
+    common::git::ensure_empty_repository_and_commit(&path)
+        .await
+        .expect("Failed to create repo");
+    common::git::tag(&path, "1.0.0")
+        .await
+        .expect("Failed to create tag");
+    common::git::run_git_command(&["commit", "--allow-empty", "-m", "feature"], &path)
+        .expect("Failed to create commit");
+    common::git::annotated_tag(&path, "1.1.0", "Release 1.1.0")
+        .await
+        .expect("Failed to create tag");
+    common::git::run_git_command(&["checkout", "-b", "next"], &path)
+        .expect("Failed to create branch");
+    common::git::run_git_command(&["commit", "--allow-empty", "-m", "next"], &path)
+        .expect("Failed to create commit");
+    common::git::tag(&path, "2.0.0")
+        .await
+        .expect("Failed to create tag");
+    common::git::checkout(&path, "main")
+        .await
+        .expect("Failed to checkout");
+    common::git::run_git_command(&["commit", "--allow-empty", "-m", "fix"], &path)
+        .expect("Failed to create commit");
+
+    common::git::run_git_command(
+        &["init", "--bare", remote.to_str().unwrap()],
+        temp_dir.path(),
+    )
+    .expect("Failed to create remote");
+    common::git::run_git_command(
+        &["push", remote.to_str().unwrap(), "main", "next", "--tags"],
+        &path,
+    )
+    .expect("Failed to push");
+    // A file URL, so the clone doesn't copy every object of the remote
+    let url = format!("file://{}", remote.display());
+    common::git::run_git_command(
+        &[
+            "clone",
+            "--no-tags",
+            "--single-branch",
+            "--branch",
+            "main",
+            &url,
+            clone.to_str().unwrap(),
+        ],
+        temp_dir.path(),
+    )
+    .expect("Failed to clone");
+
+    (temp_dir, clone)
+}
+
+#[tokio::test]
+async fn test_remote_tags() {
+    let (_temp_dir, clone) = clone_without_tags().await;
+
+    let result = calculate_version(&clone, &Config::default()).expect("Failed to calculate");
+    assert_eq!(result.to_string(), "0.0.0-alpha.0.2");
+    assert!(local_tags(&clone).is_empty());
+
+    let config = Config {
+        remote_tags: Some("origin".to_string()),
+        ..Default::default()
+    };
+    let result = calculate_version(&clone, &config).expect("Failed to calculate");
+    assert_eq!(result.to_string(), "1.1.1-alpha.0.1");
+
+    // Only tags on local commits are fetched, along with the annotated tag object
+    assert_eq!(local_tags(&clone), vec!["1.0.0", "1.1.0"]);
+    let tags = list_tags(&clone, &Config::default()).expect("Failed to list tags");
+    assert!(tags.iter().all(|tag| tag.skipped.is_none()));
+    assert!(tags
+        .iter()
+        .any(|tag| tag.tag_name == "1.1.0" && tag.annotated));
+}
+
+#[tokio::test]
+async fn test_remote_tags_respects_filters() {
+    let (_temp_dir, clone) = clone_without_tags().await;
+
+    let config = Config {
+        tag_prefix: "v".to_string(),
+        remote_tags: Some("origin".to_string()),
+        ..Default::default()
+    };
+    let result = calculate_version(&clone, &config).expect("Failed to calculate");
+    assert_eq!(result.to_string(), "0.0.0-alpha.0.2");
+    assert!(local_tags(&clone).is_empty());
+}
+
+#[tokio::test]
+async fn test_remote_tags_keeps_local_tags() {
+    let (_temp_dir, clone) = clone_without_tags().await;
+
+    // A local tag of the same name on another commit is never moved
+    common::git::tag(&clone, "1.1.0")
+        .await
+        .expect("Failed to create tag");
+
+    let config = Config {
+        remote_tags: Some("origin".to_string()),
+        ..Default::default()
+    };
+    let result = calculate_version(&clone, &config).expect("Failed to calculate");
+    assert_eq!(result.to_string(), "1.1.0");
+    assert_eq!(local_tags(&clone), vec!["1.0.0", "1.1.0"]);
+}
+
+#[tokio::test]
+async fn test_unknown_remote() {
+    let (_temp_dir, clone) = clone_without_tags().await;
+
+    let config = Config {
+        remote_tags: Some("upstream".to_string()),
+        ..Default::default()
+    };
+    let err = calculate_version(&clone, &config).unwrap_err();
+    assert!(matches!(err, TagVerError::RemoteTags(_)));
+}
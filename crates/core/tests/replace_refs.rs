@@ -0,0 +1,118 @@
+//! Replace ref tests - history grafted with `git replace --graft` is walked like git does
+
+use std::path::Path;
+
+use tagver::config::{HeightMetric, HeightSource};
+use tagver::{calculate_version, list_tags, Config};
+use tempfile::TempDir;
+use test_case::test_case;
+
+mod common;
+
+fn commit(path: &Path, message: &str) {
+    common::git::run_git_command(&["commit", "--allow-empty", "-m", message], path)
+        .expect("Failed to create commit");
+}
+
+fn rev_parse(path: &Path, rev: &str) -> String {
+    let output = std::process::Command::new("git")
+        .args(["rev-parse", rev])
+        .current_dir(path)
+        .output()
+        .expect("Failed to run git rev-parse");
+    String::from_utf8_lossy(&output.stdout).trim().to_string()
+}
+
+fn describe(path: &Path) -> String {
+    let output = std::process::Command::new("git")
+        .args(["describe", "--tags", "--abbrev=0"])
+        .current_dir(path)
+        .output()
+        .expect("Failed to run git describe");
+    String::from_utf8_lossy(&output.stdout).trim().to_string()
+}
+
+/// Old history `1.0.0` - `old 2` on `old`, and new history of three commits on `main` whose
+/// root is grafted onto `old`, with a commit-graph written before the graft.
+async fn grafted_repo() -> TempDir {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let path = temp_dir.path();
+
+    common::git::ensure_empty_repository(path)
+        .await
+        .expect("Failed to create repo");
+    common::git::run_git_command(&["checkout", "-b", "old"], path)
+        .expect("Failed to create branch");
+    commit(path, "old 1");
+    common::git::tag(path, "1.0.0")
+        .await
+        .expect("Failed to create tag");
+    commit(path, "old 2");
+
+    common::git::run_git_command(&["checkout", "--orphan", "main"], path)
+        .expect("Failed to create branch");
+    for message in ["new 1", "new 2", "new 3"] {
+        commit(path, message);
+    }
+    common::git::run_git_command(&["commit-graph", "write", "--reachable"], path)
+        .expect("Failed to write commit-graph");
+
+    let new_root = rev_parse(path, "main~2");
+    common::git::run_git_command(&["replace", "--graft", &new_root, "old"], path)
+        .expect("Failed to graft");
+
+    temp_dir
+}
+
+#[test_case(HeightMetric::FirstParent)]
+#[test_case(HeightMetric::MinVer)]
+#[test_case(HeightMetric::Ancestry)]
+#[tokio::test]
+async fn test_replace_refs_are_followed(metric: HeightMetric) {
+    let temp_dir = grafted_repo().await;
+    let path = temp_dir.path();
+
+    // git sees the tag through the graft
+    assert_eq!(describe(path), "1.0.0");
+
+    let config = Config {
+        height_metric: metric,
+        ..Default::default()
+    };
+    let result = calculate_version(path, &config).expect("Failed to calculate");
+    assert_eq!(result.to_string(), "1.0.1-alpha.0.4");
+}
+
+#[tokio::test]
+async fn test_replace_refs_disabled() {
+    let temp_dir = grafted_repo().await;
+    let path = temp_dir.path();
+
+    let config = Config {
+        use_replace_refs: false,
+        ..Default::default()
+    };
+    let result = calculate_version(path, &config).expect("Failed to calculate");
+    assert_eq!(result.to_string(), "0.0.0-alpha.0.2");
+
+    common::git::run_git_command(&["config", "core.useReplaceRefs", "false"], path)
+        .expect("Failed to configure git");
+    assert_eq!(describe(path), "");
+    let result = calculate_version(path, &Config::default()).expect("Failed to calculate");
+    assert_eq!(result.to_string(), "0.0.0-alpha.0.2");
+}
+
+#[tokio::test]
+async fn test_replace_refs_reachability_and_commit_count() {
+    let temp_dir = grafted_repo().await;
+    let path = temp_dir.path();
+
+    let tags = list_tags(path, &Config::default()).expect("Failed to list tags");
+    assert_eq!(tags.len(), 1);
+    assert!(tags[0].reachable);
+
+    let mut config = Config::default();
+    config.height_format.source = HeightSource::CommitCount;
+    let result = calculate_version(path, &config).expect("Failed to calculate");
+    assert_eq!(result.to_string(), "1.0.1-alpha.0.5");
+}
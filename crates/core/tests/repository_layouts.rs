@@ -0,0 +1,173 @@
+//! Repository layout tests - linked worktrees, submodules and bare mirrors
+
+use tagver::{calculate_version, Config, TagVerError};
+use tempfile::TempDir;
+
+mod common;
+
+#[tokio::test]
+async fn test_linked_worktree_uses_own_head() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let path = temp_dir.path().join("main");
+    let worktree = temp_dir.path().join("worktree");
+
+    common::git::ensure_empty_repository_and_commit(&path)
+        .await
+        .expect("Failed to create repo");
+    common::git::tag(&path, "1.0.0")
+        .await
+        .expect("Failed to create tag");
+    common::git::run_git_command(
+        &[
+            "worktree",
+            "add",
+            "-b",
+            "feature",
+            worktree.to_str().unwrap(),
+        ],
+        &path,
+    )
+    .expect("Failed to add worktree");
+
+    // Move the main worktree ahead; the linked worktree stays on the tag
+    common::git::commit(&path)
+        .await
+        .expect("Failed to create commit");
+    common::git::commit(&path)
+        .await
+        .expect("Failed to create commit");
+
+    let result = calculate_version(&worktree, &Config::default()).expect("Failed to calculate");
+    assert_eq!(result.to_string(), "1.0.0");
+
+    let result = calculate_version(&path, &Config::default()).expect("Failed to calculate");
+    assert_eq!(result.to_string(), "1.0.1-alpha.0.2");
+
+    // Tags created in a worktree are shared with the main repository
+    common::git::tag(&worktree, "1.1.0-beta.1")
+        .await
+        .expect("Failed to create tag");
+    let result = calculate_version(&worktree, &Config::default()).expect("Failed to calculate");
+    assert_eq!(result.to_string(), "1.1.0-beta.1");
+}
+
+#[tokio::test]
+async fn test_submodule() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let library = temp_dir.path().join("library");
+    let app = temp_dir.path().join("app");
+
+    common::git::ensure_empty_repository_and_commit(&library)
+        .await
+        .expect("Failed to create repo");
+    common::git::tag(&library, "2.0.0")
+        .await
+        .expect("Failed to create tag");
+
+    common::git::ensure_empty_repository_and_commit(&app)
+        .await
+        .expect("Failed to create repo");
+    common::git::tag(&app, "1.0.0")
+        .await
+        .expect("Failed to create tag");
+    common::git::run_git_command(
+        &[
+            "-c",
+            "protocol.file.allow=always",
+            "submodule",
+            "add",
+            library.to_str().unwrap(),
+            "library",
+        ],
+        &app,
+    )
+    .expect("Failed to add submodule");
+    common::git::run_git_command(&["commit", "-m", "Add library"], &app)
+        .expect("Failed to commit submodule");
+
+    let submodule = app.join("library");
+
+    // The submodule's own history and tags by default
+    let result = calculate_version(&submodule, &Config::default()).expect("Failed to calculate");
+    assert_eq!(result.to_string(), "2.0.0");
+
+    // The superproject's on request
+    let config = Config {
+        use_superproject: true,
+        ..Default::default()
+    };
+    let result = calculate_version(&submodule, &config).expect("Failed to calculate");
+    assert_eq!(result.to_string(), "1.0.1-alpha.0.1");
+
+    // Outside a submodule the option has no effect
+    let result = calculate_version(&app, &config).expect("Failed to calculate");
+    assert_eq!(result.to_string(), "1.0.1-alpha.0.1");
+}
+
+#[tokio::test]
+async fn test_bare_mirror_with_revision() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let path = temp_dir.path().join("origin");
+    let mirror = temp_dir.path().join("mirror.git");
+
+    common::git::ensure_empty_repository_and_commit(&path)
+        .await
+        .expect("Failed to create repo");
+    common::git::tag(&path, "1.0.0")
+        .await
+        .expect("Failed to create tag");
+    common::git::run_git_command(&["checkout", "-b", "release"], &path)
+        .expect("Failed to create branch");
+    common::git::run_git_command(&["commit", "--allow-empty", "-m", "release"], &path)
+        .expect("Failed to create commit");
+    common::git::checkout(&path, "main")
+        .await
+        .expect("Failed to checkout");
+
+    common::git::run_git_command(
+        &[
+            "clone",
+            "--mirror",
+            path.to_str().unwrap(),
+            mirror.to_str().unwrap(),
+        ],
+        temp_dir.path(),
+    )
+    .expect("Failed to clone mirror");
+
+    // Without a revision, the mirror's HEAD is used
+    let result = calculate_version(&mirror, &Config::default()).expect("Failed to calculate");
+    assert_eq!(result.to_string(), "1.0.0");
+
+    let config = Config {
+        revision: Some("release".to_string()),
+        build_metadata: Some("{branch}".to_string()),
+        ..Default::default()
+    };
+    let result = calculate_version(&mirror, &config).expect("Failed to calculate");
+    assert_eq!(result.to_string(), "1.0.1-alpha.0.1+release");
+
+    let config = Config {
+        revision: Some("1.0.0".to_string()),
+        ..Default::default()
+    };
+    let result = calculate_version(&mirror, &config).expect("Failed to calculate");
+    assert_eq!(result.to_string(), "1.0.0");
+}
+
+#[tokio::test]
+async fn test_invalid_revision() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let path = temp_dir.path();
+
+    common::git::ensure_empty_repository_and_commit(path)
+        .await
+        .expect("Failed to create repo");
+
+    let config = Config {
+        revision: Some("does-not-exist".to_string()),
+        ..Default::default()
+    };
+    let err = calculate_version(path, &config).unwrap_err();
+    assert!(matches!(err, TagVerError::InvalidRevision(_)));
+}
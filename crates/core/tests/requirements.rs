@@ -0,0 +1,108 @@
+//! Version requirement tests - ranges, stability and pre-release matching
+
+use tagver::requirement::{PrereleaseMatching, Requirement};
+use tagver::{calculate_version, CalculationResult, Config, TagVerError, Version};
+use tempfile::TempDir;
+use test_case::test_case;
+
+mod common;
+
+fn result(version: &str) -> CalculationResult {
+    CalculationResult {
+        version: Version::parse(version).unwrap(),
+        height: 0,
+        is_from_tag: false,
+        work_dir: ".".into(),
+    }
+}
+
+#[test_case(">=2.0.0, <3", "2.3.1", true, true; "release in range")]
+#[test_case(">=2.0.0, <3", "3.0.0", false, false; "release above range")]
+#[test_case(">=2.0.0, <3", "2.1.0-alpha.0.3", false, true; "pre-release in range")]
+#[test_case(">=2.0.0, <3", "2.0.0-rc.1", false, false; "pre-release below lower bound")]
+#[test_case(">=2.0.0, <3", "3.0.0-rc.1", false, false; "pre-release of upper bound")]
+#[test_case("<3.0.0", "3.0.0-rc.1", false, true; "pre-release below full upper bound")]
+#[test_case("<3.0.0-rc.2", "3.0.0-rc.1", true, true; "pre-release upper bound")]
+#[test_case(">=2.0.0-rc.1", "2.0.0-rc.2", true, true; "pre-release of same version")]
+#[test_case("<=1.2", "1.2.9-beta.1", false, true; "partial upper bound")]
+#[test_case("=1.2", "1.2.0-alpha.0", false, true; "partial exact")]
+#[test_case(">1.2", "1.3.0-alpha.0", false, true; "partial greater")]
+#[test_case("~1.2.3", "1.2.4-alpha.0", false, true; "tilde")]
+#[test_case("~1.2.3", "1.3.0-alpha.0", false, false; "tilde upper bound")]
+#[test_case("^0.2.3", "0.2.9-alpha.0", false, true; "caret zero major")]
+#[test_case("^0.2.3", "0.3.0-alpha.0", false, false; "caret zero major upper bound")]
+#[test_case("1.*", "1.9.0-alpha.0", false, true; "wildcard")]
+#[test_case("*", "0.0.0-alpha.0", false, true; "any")]
+#[test_case("^1.2.3", "1.4.0+build.5", true, true; "build metadata ignored")]
+fn test_satisfies(req: &str, version: &str, strict: bool, include: bool) {
+    let req = req.parse().unwrap();
+    let result = result(version);
+
+    assert_eq!(result.satisfies(&req, PrereleaseMatching::Strict), strict);
+    assert_eq!(result.satisfies(&req, PrereleaseMatching::Include), include);
+}
+
+#[test]
+fn test_require_stable() {
+    let requirement = Requirement {
+        require_stable: true,
+        ..Default::default()
+    };
+
+    assert!(result("2.0.0").check(&requirement).is_ok());
+    let err = result("2.0.0-rc.1").check(&requirement).unwrap_err();
+    assert!(matches!(err, TagVerError::RequirementNotMet(_)));
+    assert_eq!(
+        err.to_string(),
+        "Version requirement not met: 2.0.0-rc.1 is a pre-release, but a stable release is required"
+    );
+}
+
+#[test]
+fn test_failure_messages() {
+    let requirement = Requirement {
+        version_req: Some(">=2.0.0, <3".parse().unwrap()),
+        ..Default::default()
+    };
+
+    let err = result("1.4.0").check(&requirement).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Version requirement not met: 1.4.0 does not satisfy '>=2.0.0, <3'"
+    );
+
+    // Point out why a pre-release in range doesn't match
+    let err = result("2.1.0-alpha.0.3").check(&requirement).unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("include pre-releases to compare them by precedence"));
+}
+
+#[tokio::test]
+async fn test_calculated_version() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let path = temp_dir.path();
+    common::git::ensure_empty_repository_and_commit(path)
+        .await
+        .expect("Failed to create repo");
+    common::git::tag(path, "2.0.0")
+        .await
+        .expect("Failed to create tag");
+    common::git::commit(path).await.expect("Failed to commit");
+
+    let result = calculate_version(path, &Config::default()).expect("Failed to calculate");
+    assert_eq!(result.to_string(), "2.0.1-alpha.0.1");
+
+    let requirement = Requirement {
+        version_req: Some(">=2.0.0, <3".parse().unwrap()),
+        require_stable: false,
+        prerelease: PrereleaseMatching::Include,
+    };
+    assert!(result.check(&requirement).is_ok());
+    assert!(result
+        .check(&Requirement {
+            require_stable: true,
+            ..requirement
+        })
+        .is_err());
+}
@@ -0,0 +1,118 @@
+//! Tag listing tests - reachability, annotation and skip diagnostics
+
+use tagver::tags::SkipReason;
+use tagver::{calculate_version, list_tags, Config};
+use tempfile::TempDir;
+
+mod common;
+
+#[tokio::test]
+async fn test_list_tags() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let path = temp_dir.path();
+
+    common::git::ensure_empty_repository_and_commit(path)
+        .await
+        .expect("Failed to create repo");
+    common::git::tag(path, "1.0.0")
+        .await
+        .expect("Failed to create tag");
+    common::git::annotated_tag(path, "1.1.0", "Release 1.1.0")
+        .await
+        .expect("Failed to create tag");
+
+    // A tag on an abandoned branch
+    common::git::run_git_command(&["checkout", "-b", "abandoned"], path)
+        .expect("Failed to create branch");
+    common::git::run_git_command(&["commit", "--allow-empty", "-m", "abandoned"], path)
+        .expect("Failed to create commit");
+    common::git::tag(path, "2.0.0")
+        .await
+        .expect("Failed to create tag");
+    common::git::checkout(path, "main")
+        .await
+        .expect("Failed to checkout");
+
+    common::git::commit(path)
+        .await
+        .expect("Failed to create commit");
+    common::git::run_git_command(&["tag", "1.2.0", "HEAD^{tree}"], path)
+        .expect("Failed to tag tree");
+    common::git::tag(path, "latest")
+        .await
+        .expect("Failed to create tag");
+
+    let tags = list_tags(path, &Config::default()).expect("Failed to list tags");
+    let summary: Vec<_> = tags
+        .iter()
+        .map(|tag| {
+            (
+                tag.tag_name.as_str(),
+                tag.reachable,
+                tag.annotated,
+                tag.skipped.clone(),
+            )
+        })
+        .collect();
+
+    assert_eq!(
+        summary,
+        vec![
+            ("1.0.0", true, false, None),
+            ("1.1.0", true, true, None),
+            (
+                "1.2.0",
+                false,
+                false,
+                Some(SkipReason::NotACommit("tree".to_string()))
+            ),
+            ("2.0.0", false, false, None),
+            ("latest", true, false, Some(SkipReason::InvalidVersion)),
+        ]
+    );
+
+    // The tree tag is ignored rather than treated as a version
+    let result = calculate_version(path, &Config::default()).expect("Failed to calculate");
+    assert_eq!(result.to_string(), "1.1.1-alpha.0.1");
+}
+
+#[tokio::test]
+async fn test_list_tags_skip_reasons() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let path = temp_dir.path();
+
+    common::git::ensure_empty_repository_and_commit(path)
+        .await
+        .expect("Failed to create repo");
+    common::git::tag(path, "v1.0.0")
+        .await
+        .expect("Failed to create tag");
+    common::git::tag(path, "v1.1.0-nightly.1")
+        .await
+        .expect("Failed to create tag");
+    common::git::tag(path, "1.2.0")
+        .await
+        .expect("Failed to create tag");
+
+    let config = Config {
+        tag_prefix: "v".to_string(),
+        exclude_tag_patterns: vec!["*-nightly.*".parse().unwrap()],
+        ..Default::default()
+    };
+
+    let tags = list_tags(path, &config).expect("Failed to list tags");
+    let reasons: Vec<_> = tags
+        .iter()
+        .map(|tag| (tag.tag_name.as_str(), tag.skipped.clone()))
+        .collect();
+
+    assert_eq!(
+        reasons,
+        vec![
+            ("1.2.0", Some(SkipReason::NoMatch)),
+            ("v1.0.0", None),
+            ("v1.1.0-nightly.1", Some(SkipReason::Excluded)),
+        ]
+    );
+    assert_eq!(tags[1].version.as_ref().unwrap().to_string(), "1.0.0");
+}
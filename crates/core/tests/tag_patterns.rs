@@ -0,0 +1,103 @@
+//! Tag pattern tests - regex and glob alternatives to a literal prefix
+
+use tagver::config::TagPattern;
+use tempfile::TempDir;
+use test_case::test_case;
+
+mod common;
+
+fn patterns(patterns: &[&str]) -> Vec<TagPattern> {
+    patterns
+        .iter()
+        .map(|p| p.parse().expect("Failed to parse tag pattern"))
+        .collect()
+}
+
+#[test_case("release-1.2.3", &["release-*"], "1.2.3")]
+#[test_case("myapp@1.2.3", &["myapp@*"], "1.2.3")]
+#[test_case("v1.2.3-linux", &["v*-linux"], "1.2.3")]
+#[test_case("1.2.3/stable", &["*/stable"], "1.2.3")]
+#[test_case("v1.2.3-linux", &[r"^v(?<version>\d+\.\d+\.\d+)-linux$"], "1.2.3")]
+#[test_case("rel/2.0.0", &["prefix:release-", "prefix:rel/"], "2.0.0")]
+#[test_case("other-1.0.0", &["release-*"], "0.0.0-alpha.0")]
+#[tokio::test]
+async fn test_tag_pattern(tag_name: &str, tag_patterns: &[&str], expected_version: &str) {
+    use tagver::{calculate_version, Config};
+
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let path = temp_dir.path();
+
+    // Create repository with tag
+    common::git::ensure_empty_repository_and_commit(path)
+        .await
+        .expect("Failed to create repo");
+    common::git::tag(path, tag_name)
+        .await
+        .expect("Failed to create tag");
+
+    // Configure version calculation with patterns
+    let config = Config {
+        tag_patterns: patterns(tag_patterns),
+        ..Default::default()
+    };
+
+    // Calculate version
+    let result = calculate_version(path, &config).expect("Failed to calculate version");
+
+    // Verify the version
+    assert_eq!(result.to_string(), expected_version);
+}
+
+#[tokio::test]
+async fn test_patterns_tried_in_order() {
+    use tagver::{calculate_version, Config};
+
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let path = temp_dir.path();
+
+    common::git::ensure_empty_repository_and_commit(path)
+        .await
+        .expect("Failed to create repo");
+    // "v*" alone would yield "2.0.0-linux", a prerelease of 2.0.0
+    common::git::tag(path, "v2.0.0-linux")
+        .await
+        .expect("Failed to create tag");
+
+    let config = Config {
+        tag_patterns: patterns(&["v*-linux", "v*"]),
+        ..Default::default()
+    };
+
+    let result = calculate_version(path, &config).expect("Failed to calculate version");
+    assert_eq!(result.to_string(), "2.0.0");
+}
+
+#[tokio::test]
+async fn test_exclude_tag_pattern() {
+    use tagver::{calculate_version, Config};
+
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let path = temp_dir.path();
+
+    common::git::ensure_empty_repository_and_commit(path)
+        .await
+        .expect("Failed to create repo");
+    common::git::tag(path, "1.0.0")
+        .await
+        .expect("Failed to create tag");
+    common::git::commit(path)
+        .await
+        .expect("Failed to create commit");
+    common::git::tag(path, "2.0.0-nightly.1")
+        .await
+        .expect("Failed to create tag");
+
+    // Exclusions apply on top of the default prefix behavior
+    let config = Config {
+        exclude_tag_patterns: patterns(&["*-nightly.*"]),
+        ..Default::default()
+    };
+
+    let result = calculate_version(path, &config).expect("Failed to calculate version");
+    assert_eq!(result.to_string(), "1.0.1-alpha.0.1");
+}
@@ -0,0 +1,95 @@
+//! Tag selection tests - several version tags on one commit
+
+use std::path::Path;
+use std::process::Command;
+
+use tagver::config::{Backend, TagSelection};
+use tagver::{calculate_version, Config};
+use tempfile::TempDir;
+use test_case::test_case;
+
+mod common;
+
+/// Create an annotated tag with the given tagger date.
+fn annotated_tag_at(path: &Path, tag_name: &str, date: &str) {
+    let status = Command::new("git")
+        .args(["tag", "-a", tag_name, "-m", tag_name])
+        .env("GIT_COMMITTER_DATE", date)
+        .current_dir(path)
+        .status()
+        .expect("Failed to run git");
+    assert!(status.success(), "Failed to create tag {}", tag_name);
+}
+
+/// One commit tagged with each of `tags`, in order.
+async fn repo_with_tags(tags: &[&str]) -> TempDir {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    common::git::ensure_empty_repository_and_commit(temp_dir.path())
+        .await
+        .expect("Failed to create repo");
+    for tag in tags {
+        common::git::tag(temp_dir.path(), tag)
+            .await
+            .expect("Failed to create tag");
+    }
+    temp_dir
+}
+
+fn versions(path: &Path, selection: TagSelection) -> Vec<String> {
+    [Backend::Gix, Backend::Git]
+        .into_iter()
+        .map(|backend| {
+            let config = Config {
+                tag_selection: selection,
+                backend,
+                ..Default::default()
+            };
+            calculate_version(path, &config)
+                .expect("Failed to calculate")
+                .to_string()
+        })
+        .collect()
+}
+
+#[test_case(&["1.1.0-rc.1", "1.1.0"], TagSelection::Precedence, "1.1.0"; "release over its rc")]
+#[test_case(&["1.1.0", "1.1.0-rc.1"], TagSelection::Precedence, "1.1.0"; "release over its rc in any order")]
+#[test_case(&["1.0.0-beta.11", "1.0.0-beta.2"], TagSelection::Precedence, "1.0.0-beta.11"; "numeric identifiers")]
+#[test_case(&["1.0.0", "1.1.0-rc.1"], TagSelection::Precedence, "1.1.0-rc.1"; "higher prerelease")]
+#[test_case(&["1.0.0", "1.1.0-rc.1"], TagSelection::PreferRtm, "1.0.0"; "prefer rtm")]
+#[test_case(&["1.1.0-rc.1", "1.1.0-rc.2"], TagSelection::PreferRtm, "1.1.0-rc.2"; "prefer rtm without rtm")]
+#[tokio::test]
+async fn test_selection(tags: &[&str], selection: TagSelection, expected: &str) {
+    let temp_dir = repo_with_tags(tags).await;
+
+    for version in versions(temp_dir.path(), selection) {
+        assert_eq!(version, expected);
+    }
+}
+
+#[tokio::test]
+async fn test_newest_annotated_tag() {
+    let temp_dir = repo_with_tags(&["3.0.0"]).await;
+    let path = temp_dir.path();
+    annotated_tag_at(path, "2.0.0", "2024-01-02T00:00:00Z");
+    annotated_tag_at(path, "1.0.0", "2024-03-04T00:00:00Z");
+    annotated_tag_at(path, "1.5.0", "2024-02-03T00:00:00Z");
+
+    for version in versions(path, TagSelection::Newest) {
+        assert_eq!(version, "1.0.0");
+    }
+    for version in versions(path, TagSelection::Precedence) {
+        assert_eq!(version, "3.0.0");
+    }
+}
+
+#[tokio::test]
+async fn test_selection_below_head() {
+    let temp_dir = repo_with_tags(&["1.1.0-rc.1", "1.1.0"]).await;
+    common::git::commit(temp_dir.path())
+        .await
+        .expect("Failed to commit");
+
+    for version in versions(temp_dir.path(), TagSelection::Precedence) {
+        assert_eq!(version, "1.1.1-alpha.0.1");
+    }
+}
@@ -0,0 +1,179 @@
+//! Tag kind tests - annotated-only, signed-only and verified SSH signatures
+
+use std::path::{Path, PathBuf};
+
+use tagver::config::TagRequirement;
+use tagver::{calculate_version, Config, TagVerError};
+use tempfile::TempDir;
+
+mod common;
+
+/// Generate an ed25519 key pair, returning the private key path and public key line.
+fn ssh_key(dir: &Path, name: &str) -> (PathBuf, String) {
+    let key = dir.join(name);
+    let output = std::process::Command::new("ssh-keygen")
+        .args(["-q", "-t", "ed25519", "-N", "", "-C", name, "-f"])
+        .arg(&key)
+        .output()
+        .expect("Failed to run ssh-keygen");
+    assert!(output.status.success(), "ssh-keygen failed");
+
+    let public = std::fs::read_to_string(key.with_extension("pub")).expect("Missing public key");
+    (key, public.trim().to_string())
+}
+
+fn signed_tag(path: &Path, key: &Path, tag_name: &str) {
+    common::git::run_git_command(
+        &[
+            "-c",
+            "gpg.format=ssh",
+            "-c",
+            &format!("user.signingkey={}", key.display()),
+            "tag",
+            "-s",
+            "-m",
+            "Signed release",
+            tag_name,
+        ],
+        path,
+    )
+    .expect("Failed to create signed tag");
+}
+
+/// A repository with tags of every kind, newest last:
+/// `1.0.0` signed by `release`, `1.1.0` signed by `other`, `1.2.0` annotated, `1.3.0` lightweight.
+async fn repo_with_tag_kinds() -> (TempDir, PathBuf, String, String) {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let path = temp_dir.path().join("repo");
+    let (release_key, release_public) = ssh_key(temp_dir.path(), "release");
+    let (other_key, other_public) = ssh_key(temp_dir.path(), "other");
+
+    common::git::ensure_empty_repository_and_commit(&path)
+        .await
+        .expect("Failed to create repo");
+    signed_tag(&path, &release_key, "1.0.0");
+    common::git::commit(&path)
+        .await
+        .expect("Failed to create commit");
+    signed_tag(&path, &other_key, "1.1.0");
+    common::git::commit(&path)
+        .await
+        .expect("Failed to create commit");
+    common::git::annotated_tag(&path, "1.2.0", "Unsigned release")
+        .await
+        .expect("Failed to create tag");
+    common::git::commit(&path)
+        .await
+        .expect("Failed to create commit");
+    common::git::tag(&path, "1.3.0")
+        .await
+        .expect("Failed to create tag");
+    common::git::commit(&path)
+        .await
+        .expect("Failed to create commit");
+
+    (temp_dir, path, release_public, other_public)
+}
+
+#[tokio::test]
+async fn test_tag_requirement() {
+    let (_temp_dir, path, _, _) = repo_with_tag_kinds().await;
+
+    for (requirement, expected_version) in [
+        (TagRequirement::Any, "1.3.1-alpha.0.1"),
+        (TagRequirement::Annotated, "1.2.1-alpha.0.2"),
+        (TagRequirement::Signed, "1.1.1-alpha.0.3"),
+    ] {
+        let config = Config {
+            tag_requirement: requirement,
+            ..Default::default()
+        };
+        let result = calculate_version(&path, &config).expect("Failed to calculate version");
+        assert_eq!(result.to_string(), expected_version);
+    }
+}
+
+#[tokio::test]
+async fn test_verified_tags() {
+    let (temp_dir, path, release_public, _) = repo_with_tag_kinds().await;
+
+    let allowed_signers = temp_dir.path().join("allowed_signers");
+    std::fs::write(
+        &allowed_signers,
+        format!(
+            "# Release keys\nrelease@example.com namespaces=\"git\" {}\n",
+            release_public
+        ),
+    )
+    .expect("Failed to write allowed signers");
+
+    // Only the tag signed by the allowed key counts
+    let config = Config {
+        tag_requirement: TagRequirement::Verified,
+        allowed_signers: Some(allowed_signers.clone()),
+        ..Default::default()
+    };
+    let result = calculate_version(&path, &config).expect("Failed to calculate version");
+    assert_eq!(result.to_string(), "1.0.1-alpha.0.4");
+
+    // Falls back to git's configuration
+    common::git::run_git_command(
+        &[
+            "config",
+            "gpg.ssh.allowedSignersFile",
+            allowed_signers.to_str().unwrap(),
+        ],
+        &path,
+    )
+    .expect("Failed to configure git");
+    let config = Config {
+        tag_requirement: TagRequirement::Verified,
+        ..Default::default()
+    };
+    let result = calculate_version(&path, &config).expect("Failed to calculate version");
+    assert_eq!(result.to_string(), "1.0.1-alpha.0.4");
+}
+
+#[tokio::test]
+async fn test_verified_tags_namespace() {
+    let (temp_dir, path, release_public, other_public) = repo_with_tag_kinds().await;
+
+    // The release key may only sign files, not git objects
+    let allowed_signers = temp_dir.path().join("allowed_signers");
+    std::fs::write(
+        &allowed_signers,
+        format!(
+            "release@example.com namespaces=\"file\" {}\nother@example.com {}\n",
+            release_public, other_public
+        ),
+    )
+    .expect("Failed to write allowed signers");
+
+    let config = Config {
+        tag_requirement: TagRequirement::Verified,
+        allowed_signers: Some(allowed_signers),
+        ..Default::default()
+    };
+    let result = calculate_version(&path, &config).expect("Failed to calculate version");
+    assert_eq!(result.to_string(), "1.1.1-alpha.0.3");
+}
+
+#[tokio::test]
+async fn test_verified_tags_without_allowed_signers() {
+    let (temp_dir, path, _, _) = repo_with_tag_kinds().await;
+
+    let config = Config {
+        tag_requirement: TagRequirement::Verified,
+        ..Default::default()
+    };
+    let err = calculate_version(&path, &config).unwrap_err();
+    assert!(matches!(err, TagVerError::InvalidAllowedSigners(_)));
+
+    let config = Config {
+        tag_requirement: TagRequirement::Verified,
+        allowed_signers: Some(temp_dir.path().join("missing")),
+        ..Default::default()
+    };
+    let err = calculate_version(&path, &config).unwrap_err();
+    assert!(matches!(err, TagVerError::InvalidAllowedSigners(_)));
+}